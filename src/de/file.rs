@@ -0,0 +1,41 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+/// Read the contents of the file at `path`, trimmed of a trailing newline.
+pub fn read(path: &Path) -> std::io::Result<String> {
+    let mut contents = std::fs::read_to_string(path)?;
+
+    if contents.ends_with('\n') {
+        contents.pop();
+
+        if contents.ends_with('\r') {
+            contents.pop();
+        }
+    }
+
+    Ok(contents)
+}
+
+/// The contents of the files read during a deserialization, so that each file is read once even
+/// if deserializing again to collect errors.
+#[derive(Debug, Default)]
+pub struct Files(Mutex<HashMap<PathBuf, String>>);
+
+impl Files {
+    /// Read the file at `path`, see [`read`].
+    pub fn read(&self, path: &Path) -> std::io::Result<String> {
+        let mut files = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(contents) = files.get(path) {
+            return Ok(contents.clone());
+        }
+
+        let contents = read(path)?;
+        files.insert(path.to_owned(), contents.clone());
+
+        Ok(contents)
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use crate::{Error, Result};
+
+/// The maximum length of an interpolated value, in bytes.
+pub const MAX_LENGTH: usize = 1 << 20;
+
+/// The variables that references are resolved against, see [interpolation].
+///
+/// [interpolation]: crate#interpolation
+#[derive(Debug, Default)]
+pub struct Variables(HashMap<String, String>);
+
+impl Variables {
+    pub fn new(variables: HashMap<String, String>) -> Self {
+        Self(variables)
+    }
+
+    /// Replace the references in `value`, the value of the variable named `name`.
+    ///
+    /// Returns the interpolated value and the names of the variables whose value it contains.
+    pub fn interpolate<'a>(
+        &'a self,
+        name: &'a str,
+        value: &'a str,
+    ) -> Result<(String, Vec<&'a str>)> {
+        let mut interpolation = Interpolation {
+            variables: self,
+            chain: vec![name],
+            resolved: Vec::new(),
+            cache: HashMap::new(),
+        };
+
+        let value = interpolation.interpolate(value)?;
+
+        Ok((value, interpolation.resolved))
+    }
+}
+
+struct Interpolation<'a> {
+    variables: &'a Variables,
+    /// The variables being interpolated, each referenced by the previous one.
+    chain: Vec<&'a str>,
+    /// The variables whose value was resolved.
+    resolved: Vec<&'a str>,
+    /// The interpolated values of the variables resolved so far, each is only interpolated once.
+    cache: HashMap<&'a str, String>,
+}
+
+impl<'a> Interpolation<'a> {
+    fn interpolate(&mut self, value: &'a str) -> Result<String> {
+        let mut interpolated = String::new();
+        let mut rest = value;
+
+        while let Some(position) = rest.find('$') {
+            interpolated.push_str(&rest[..position]);
+            rest = &rest[position..];
+
+            if let Some(after) = rest.strip_prefix("$$") {
+                interpolated.push('$');
+                rest = after;
+            } else if rest.starts_with("${") {
+                let end = closing_brace(rest)
+                    .ok_or_else(|| Error::invalid_interpolation("unterminated reference"))?;
+
+                interpolated.push_str(&self.reference(&rest[2..end])?);
+                rest = &rest[end + 1..];
+
+                if interpolated.len() > MAX_LENGTH {
+                    return Err(Error::interpolation_too_long(MAX_LENGTH));
+                }
+            } else {
+                interpolated.push('$');
+                rest = &rest[1..];
+            }
+        }
+
+        interpolated.push_str(rest);
+
+        Ok(interpolated)
+    }
+
+    /// Resolve the contents of a `${...}` reference.
+    fn reference(&mut self, reference: &'a str) -> Result<String> {
+        let (name, modifier) = match reference.find(':') {
+            Some(position) => (&reference[..position], Some(&reference[position + 1..])),
+            None => (reference, None),
+        };
+
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '_')
+        {
+            return Err(Error::invalid_interpolation("invalid variable name"));
+        }
+
+        let modifier = match modifier.map(|modifier| modifier.split_at_checked(1)) {
+            None => None,
+            Some(Some((char @ ("-" | "?"), argument))) => Some((char, argument)),
+            Some(_) => {
+                return Err(Error::invalid_interpolation(
+                    "unsupported modifier, expected `:-` or `:?`",
+                ))
+            }
+        };
+
+        let value = self.resolve(name)?.filter(|value| !value.is_empty());
+
+        match (value, modifier) {
+            (Some(value), _) => Ok(value),
+            (None, None) => Ok(String::new()),
+            (None, Some(("-", default))) => self.interpolate(default),
+            (None, Some((_, message))) => {
+                let mut chain = self.chain.clone();
+                chain.push(name);
+
+                Err(Error::missing_reference(&chain, message))
+            }
+        }
+    }
+
+    /// The interpolated value of the variable named `name`, if set.
+    fn resolve(&mut self, name: &'a str) -> Result<Option<String>> {
+        if self.chain.contains(&name) {
+            let mut chain = self.chain.clone();
+            chain.push(name);
+
+            return Err(Error::reference_cycle(&chain));
+        }
+
+        if let Some(value) = self.cache.get(name) {
+            return Ok(Some(value.clone()));
+        }
+
+        let Some(value) = self.variables.0.get(name) else {
+            return Ok(None);
+        };
+
+        self.resolved.push(name);
+
+        self.chain.push(name);
+        let value = self.interpolate(value)?;
+        self.chain.pop();
+
+        self.cache.insert(name, value.clone());
+
+        Ok(Some(value))
+    }
+}
+
+/// The index of the brace closing the reference `value` starts with, if any.
+fn closing_brace(value: &str) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut index = 0;
+
+    while let Some(rest) = value.get(index..).filter(|rest| !rest.is_empty()) {
+        if rest.starts_with("$$") {
+            index += 2;
+        } else if rest.starts_with("${") {
+            depth += 1;
+            index += 2;
+        } else if rest.starts_with('}') {
+            depth -= 1;
+
+            if depth == 0 {
+                return Some(index);
+            }
+
+            index += 1;
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Variables;
+
+    fn variables(variables: &[(&str, &str)]) -> Variables {
+        Variables::new(
+            variables
+                .iter()
+                .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn interpolate() {
+        let variables = variables(&[
+            ("USER", "admin"),
+            ("HOST", "localhost"),
+            ("EMPTY", ""),
+            ("URL", "${USER}@${HOST}"),
+        ]);
+
+        let interpolate = |value| variables.interpolate("VALUE", value).unwrap().0;
+
+        assert_eq!(interpolate("plain"), "plain");
+        assert_eq!(
+            interpolate("${USER}@${HOST}:${PORT:-5432}"),
+            "admin@localhost:5432"
+        );
+        assert_eq!(interpolate("${EMPTY:-default}"), "default");
+        assert_eq!(interpolate("${MISSING}"), "");
+        assert_eq!(interpolate("${MISSING:-${USER:-x}}"), "admin");
+        assert_eq!(interpolate("${USER:?required}"), "admin");
+        assert_eq!(interpolate("https://${URL}/"), "https://admin@localhost/");
+        assert_eq!(
+            interpolate("$$HOST $${HOST} $HOST $"),
+            "$HOST ${HOST} $HOST $"
+        );
+        assert_eq!(interpolate("${MISSING:-a}b}"), "ab}");
+
+        assert_eq!(
+            variables
+                .interpolate("VALUE", "${URL} ${MISSING}")
+                .unwrap()
+                .1,
+            ["URL", "USER", "HOST"]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let variables = variables(&[("A", "${B}"), ("B", "${A}"), ("C", "${D:?must be set}")]);
+
+        let error = |value| {
+            variables
+                .interpolate("VALUE", value)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error("${A}"),
+            "reference cycle: `VALUE` -> `A` -> `B` -> `A`"
+        );
+        assert_eq!(error("${VALUE}"), "reference cycle: `VALUE` -> `VALUE`");
+        assert_eq!(
+            error("${C}"),
+            "missing variable `D`, referenced through `VALUE` -> `C` -> `D`: must be set"
+        );
+        assert_eq!(
+            error("${D:?}"),
+            "missing variable `D`, referenced through `VALUE` -> `D`"
+        );
+        assert_eq!(
+            error("${A"),
+            "invalid interpolation: unterminated reference"
+        );
+        assert_eq!(error("${}"), "invalid interpolation: invalid variable name");
+        assert_eq!(
+            error("${A#b}"),
+            "invalid interpolation: invalid variable name"
+        );
+        assert_eq!(
+            error("${A:=b}"),
+            "invalid interpolation: unsupported modifier, expected `:-` or `:?`"
+        );
+    }
+
+    #[test]
+    fn too_long() {
+        // Each variable repeats the previous one twice, doubling its length.
+        let chain: Vec<(String, String)> = (1..64)
+            .map(|index| {
+                (
+                    format!("V{index}"),
+                    format!("${{V{}}}${{V{}}}", index - 1, index - 1),
+                )
+            })
+            .chain([("V0".to_owned(), "x".to_owned())])
+            .collect();
+
+        let variables = Variables::new(chain.into_iter().collect());
+
+        assert_eq!(
+            variables.interpolate("VALUE", "${V16}").unwrap().0.len(),
+            1 << 16
+        );
+        assert_eq!(
+            variables
+                .interpolate("VALUE", "${V63}")
+                .unwrap_err()
+                .to_string(),
+            format!("interpolated value longer than {} bytes", super::MAX_LENGTH)
+        );
+    }
+}
@@ -5,8 +5,46 @@ use std::{
 
 use crate::{Error, Result};
 
+#[derive(Clone)]
 pub struct Key<'de>(Cow<'de, OsStr>);
 
+impl<'de> Key<'de> {
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.to_str()
+    }
+
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        self.0.to_string_lossy()
+    }
+
+    /// Remove `prefix` from the key, if the key starts with it.
+    pub fn strip_prefix(self, prefix: &str) -> Option<Self> {
+        let len = self.as_str()?.strip_prefix(prefix)?.len();
+        let at = self.as_str()?.len() - len;
+
+        Some(self.split_off(at))
+    }
+
+    /// Remove the first `at` bytes of the key.
+    ///
+    /// # Panics
+    /// Panics if the key is not valid unicode or if `at` is not on a char boundary.
+    pub fn split_off(self, at: usize) -> Self {
+        match self.0 {
+            Cow::Borrowed(value) => Self::from(&value.to_str().expect("valid unicode")[at..]),
+            Cow::Owned(value) => {
+                Self::from(value.to_str().expect("valid unicode")[at..].to_owned())
+            }
+        }
+    }
+}
+
+impl PartialEq<str> for Key<'_> {
+    fn eq(&self, other: &str) -> bool {
+        *self.0 == *other
+    }
+}
+
 impl<'de> From<Cow<'de, OsStr>> for Key<'de> {
     fn from(value: Cow<'de, OsStr>) -> Self {
         Self(value)
@@ -79,6 +117,14 @@ impl<'de> serde::de::Deserializer<'de> for Key<'de> {
         )
     }
 
+    // Required for buffering keys, e.g. for internally tagged enums.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
@@ -96,6 +142,6 @@ impl<'de> serde::de::Deserializer<'de> for Key<'de> {
     crate::de::util::unsupported_types! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
         bytes byte_buf option unit unit_struct seq tuple
-        tuple_struct map struct enum ignored_any any
+        tuple_struct map struct enum ignored_any
     }
 }
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use super::{file::Files, interpolate::Variables};
+
+/// Where a node comes from, used to give context to errors.
+#[derive(Debug, Clone, Default)]
+pub struct Location {
+    /// The variable name, or the prefix of the nested variable names for a
+    /// [`Deserializer`](super::Deserializer).
+    pub key: String,
+    /// The field path, segments are separated by dots.
+    pub path: String,
+    /// The paths of the nodes that failed to deserialize in a previous attempt when collecting
+    /// errors, which are left out.
+    pub skipped: Arc<[Box<str>]>,
+    /// The paths of the nodes that were left out but are required, which are replaced by a
+    /// [`Placeholder`](super::placeholder::Placeholder) when collecting errors.
+    pub substituted: Arc<[Box<str>]>,
+    /// The variables references are resolved against, if [interpolation] is enabled.
+    ///
+    /// [interpolation]: crate#interpolation
+    pub variables: Option<Arc<Variables>>,
+    /// The files read so far, see [`Options::file_suffix`](crate::Options::file_suffix).
+    pub files: Arc<Files>,
+}
+
+impl Location {
+    /// The location of `segment` nested under `self`.
+    pub fn nested(&self, segment: &str) -> Self {
+        Self {
+            key: format!("{}{segment}", self.key),
+            path: if self.path.is_empty() {
+                segment.to_owned()
+            } else {
+                format!("{}.{segment}", self.path)
+            },
+            skipped: Arc::clone(&self.skipped),
+            substituted: Arc::clone(&self.substituted),
+            variables: self.variables.clone(),
+            files: Arc::clone(&self.files),
+        }
+    }
+
+    /// Whether the node at this location should be left out.
+    pub fn is_skipped(&self) -> bool {
+        self.skipped.iter().any(|path| **path == *self.path)
+    }
+
+    /// Whether the node at this location should be replaced by a placeholder.
+    pub fn is_substituted(&self) -> bool {
+        self.substituted.iter().any(|path| **path == *self.path)
+    }
+}
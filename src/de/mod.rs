@@ -1,14 +1,29 @@
 #![allow(clippy::needless_doctest_main)]
 
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
 use serde::{de::value::MapDeserializer, Deserialize};
 
-use crate::{Error, Result};
+use crate::{options::DEFAULT_OPTIONS, Error, Options, Result};
 
-use self::{key::Key, value::Value};
+use self::{interpolate::Variables, node::Node};
+pub(crate) use self::{key::Key, location::Location, placeholder::Placeholder, value::Value};
 
+mod file;
+mod interpolate;
 mod key;
+mod location;
+mod node;
+mod placeholder;
+mod split;
 mod util;
 mod value;
+mod variant;
 
 /// Deserialize an instance of `T` from the environment variables of the current process.
 ///
@@ -41,7 +56,7 @@ pub fn from_env<'de, T>() -> Result<T>
 where
     T: Deserialize<'de>,
 {
-    from_iter(std::env::vars_os())
+    DEFAULT_OPTIONS.from_env()
 }
 
 /// Deserialize an instance of `T` from the environment variables of the current process with the
@@ -76,12 +91,9 @@ pub fn from_env_prefixed<'de, T>(prefix: &str) -> Result<T>
 where
     T: Deserialize<'de>,
 {
-    from_iter(
-        std::env::vars_os().filter_map(|(name, value)| match name.to_str() {
-            Some(name) => Some((std::ffi::OsString::from(name.strip_prefix(prefix)?), value)),
-            _ => None,
-        }),
-    )
+    // The same as `Options::new().prefix(prefix)`, which cannot be borrowed for `'de`.
+    Deserializer::from_iter_prefixed(&DEFAULT_OPTIONS, Some(prefix), std::env::vars_os())
+        .deserialize()
 }
 
 /// Deserialize an instance of `T` from an iterator of key-value tuple.
@@ -129,41 +141,532 @@ pub fn from_iter<'de, T>(
 where
     T: Deserialize<'de>,
 {
-    let mut deserializer =
-        EnvDeserializer::from_iter(iter.map(|(key, value)| (key.into(), value.into())));
-
-    T::deserialize(&mut deserializer)
+    DEFAULT_OPTIONS.from_iter(iter)
 }
 
-struct EnvDeserializer<'de, I: Iterator<Item = (Key<'de>, Value<'de>)>>(
-    MapDeserializer<'de, I, Error>,
-);
-
-impl<'de, I> EnvDeserializer<'de, I>
+/// Deserialize an instance of `T` from a directory holding one file per variable, such as a mounted
+/// Kubernetes ConfigMap, Docker secrets (`/run/secrets`) or systemd credentials
+/// (`$CREDENTIALS_DIRECTORY`).
+///
+/// Each regular file provides a variable named after it, whose value is the contents of the file
+/// trimmed of a trailing newline. Hidden files (i.e. whose name starts with a dot) are ignored, and
+/// so are subdirectories unless [rejected](Options::reject_subdirectories).
+///
+/// # Example
+///
+/// Assuming we have a `/run/secrets` directory holding a `DB_USER` and `DB_PASSWORD` file:
+///
+/// ```rust,no_run
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Secrets {
+///     db_user: String,
+///     db_password: String,
+/// }
+///
+/// let secrets: Secrets = de_env::from_dir("/run/secrets")?;
+///
+/// println!("{secrets:#?}");
+/// # Ok::<(), de_env::Error>(())
+/// ```
+///
+/// # Errors
+/// This conversion can fail if the directory or one of its files cannot be read, if trying to
+/// deserialize [unsupported types], or if `T`'s implementation of `Deserialize` decides that
+/// something is wrong with the data.
+///
+/// [unsupported types]: crate#unsupported-types
+pub fn from_dir<'de, T>(path: impl AsRef<Path>) -> Result<T>
 where
-    I: Iterator<Item = (Key<'de>, Value<'de>)>,
+    T: Deserialize<'de>,
 {
-    pub fn from_iter(iter: I) -> Self {
-        Self(MapDeserializer::new(iter))
+    DEFAULT_OPTIONS.from_dir(path)
+}
+
+/// A [`serde::Deserializer`] over a set of environment variables.
+///
+/// This is intended to be used with serde tooling that expects a deserializer, otherwise see
+/// [`Options`] or the free functions ([`from_env`], [`from_iter`], ...).
+///
+/// # Example
+///
+/// Assuming we have a `APP_TIMEOUT` and `APP_HOST` environment variable:
+///
+/// ```rust
+/// use serde::Deserialize;
+///
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     timeout: u16,
+///     host: std::net::IpAddr,
+/// }
+///
+/// # std::env::set_var("APP_TIMEOUT", "12");
+/// # std::env::set_var("APP_HOST", "127.0.0.1");
+/// let options = de_env::Options::new().prefix("APP_");
+/// let config = Config::deserialize(de_env::Deserializer::from_env(&options))?;
+///
+/// println!("{config:#?}");
+/// # Ok::<(), de_env::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Deserializer<'de> {
+    entries: Vec<(Key<'de>, Value<'de>)>,
+    options: &'de Options,
+    location: Location,
+}
+
+impl<'de> Deserializer<'de> {
+    pub(crate) fn new(
+        entries: Vec<(Key<'de>, Value<'de>)>,
+        location: Location,
+        options: &'de Options,
+    ) -> Self {
+        Self {
+            entries,
+            options,
+            location,
+        }
+    }
+
+    /// Create a deserializer over the environment variables of the current process.
+    #[must_use]
+    pub fn from_env(options: &'de Options) -> Self {
+        Self::from_iter(options, std::env::vars_os())
+    }
+
+    /// Create a deserializer over an iterator of key-value tuple.
+    ///
+    /// See [`from_iter`] for the supported iterator items.
+    pub fn from_iter(
+        options: &'de Options,
+        iter: impl Iterator<Item = (impl Into<Key<'de>>, impl Into<Value<'de>>)>,
+    ) -> Self {
+        Self::from_iter_prefixed(options, options.prefix.as_deref(), iter)
+    }
+
+    /// Create a deserializer over an iterator of key-value tuple, using `prefix` rather than that
+    /// of `options`.
+    fn from_iter_prefixed(
+        options: &'de Options,
+        prefix: Option<&str>,
+        iter: impl Iterator<Item = (impl Into<Key<'de>>, impl Into<Value<'de>>)>,
+    ) -> Self {
+        let entries = iter.map(|(key, value)| (key.into(), value.into().with_options(options)));
+
+        // References may name any variable, including those filtered out by the prefix.
+        let (entries, variables): (Vec<(Key, Value)>, _) = if options.interpolate {
+            let entries: Vec<(Key, Value)> = entries.collect();
+
+            let variables = entries
+                .iter()
+                .filter_map(|(key, value)| {
+                    Some((key.as_str()?.to_owned(), value.to_str()?.to_owned()))
+                })
+                .collect();
+
+            (entries, Some(Arc::new(Variables::new(variables))))
+        } else {
+            (entries.collect(), None)
+        };
+
+        let entries = match prefix {
+            Some(prefix) => entries
+                .into_iter()
+                .filter_map(|(key, value)| Some((key.strip_prefix(prefix)?, value)))
+                .collect(),
+            None => entries,
+        };
+
+        let location = Location {
+            key: prefix.unwrap_or_default().to_owned(),
+            variables,
+            ..Location::default()
+        };
+
+        Self::new(entries, location, options)
+    }
+
+    /// Create a deserializer over a directory holding one file per variable.
+    ///
+    /// See [`from_dir`] for how the directory is read.
+    ///
+    /// # Errors
+    /// This fails if the directory or one of its files cannot be read, or if it holds a
+    /// subdirectory and [`Options::reject_subdirectories`] is enabled.
+    pub fn from_dir(options: &'de Options, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let prefix = options.prefix.as_deref().unwrap_or_default();
+        let mut entries: Vec<(OsString, String)> = Vec::new();
+
+        let read_dir = |error| Error::read_file(path.to_owned(), error);
+
+        for entry in std::fs::read_dir(path).map_err(read_dir)? {
+            let entry = entry.map_err(read_dir)?;
+            let name = entry.file_name();
+
+            // Skip the files that would be filtered out anyway rather than reading them.
+            let Some(field) = name.to_str().and_then(|name| name.strip_prefix(prefix)) else {
+                continue;
+            };
+
+            // e.g. the `..data` directory of Kubernetes volumes.
+            if name.as_encoded_bytes().starts_with(b".") {
+                continue;
+            }
+
+            let path = entry.path();
+
+            let context = |error: Error| {
+                let location = Location {
+                    key: prefix.to_owned(),
+                    ..Location::default()
+                }
+                .nested(field);
+
+                error.with_context(location.key, location.path, None, false)
+            };
+
+            // Follow symbolic links, e.g. the files of Kubernetes volumes.
+            let metadata = std::fs::metadata(&path)
+                .map_err(|error| context(Error::read_file(path.clone(), error)))?;
+
+            if metadata.is_dir() && options.reject_subdirectories {
+                return Err(context(Error::unexpected_directory(path)));
+            }
+
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let contents =
+                file::read(&path).map_err(|error| context(Error::read_file(path, error)))?;
+
+            entries.push((name, contents));
+        }
+
+        entries.sort();
+
+        Ok(Self::from_iter(options, entries.into_iter()))
+    }
+
+    /// Deserialize an instance of `T`, collecting every error if enabled.
+    ///
+    /// Errors are collected by deserializing `T` again after each error, leaving out the node that
+    /// failed (i.e. whose path is that of the error) so that the remaining nodes are visited. If
+    /// `T` then requires that node (e.g. a missing field error), it is replaced by a placeholder so
+    /// that the fields declared after it are checked. This stops once `T` is deserialized, or when
+    /// an error cannot be recovered from (e.g. `T` rejects a placeholder).
+    pub(crate) fn deserialize<T>(self) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        if !self.options.collect_errors {
+            return T::deserialize(self);
+        }
+
+        let mut errors: Vec<Error> = Vec::new();
+        let mut skipped: Vec<Box<str>> = Vec::new();
+        let mut substituted: Vec<Box<str>> = Vec::new();
+
+        loop {
+            let mut deserializer = self.clone();
+            deserializer.location.skipped = skipped.as_slice().into();
+            deserializer.location.substituted = substituted.as_slice().into();
+
+            let error = match T::deserialize(deserializer) {
+                Ok(value) if errors.is_empty() => return Ok(value),
+                Ok(_) => break,
+                Err(error) => error,
+            };
+
+            let Some(path) = error.path().filter(|path| !path.is_empty()) else {
+                errors.push(error);
+                break;
+            };
+
+            // Whether `path` is that of a node enclosing the node at `other`.
+            let encloses = |other: &str| {
+                other
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            };
+
+            if let Some(position) = skipped.iter().position(|other| **other == *path) {
+                substituted.push(skipped.remove(position));
+            } else if substituted.iter().any(|other| **other == *path) {
+                break;
+            } else if skipped
+                .iter()
+                .chain(&substituted)
+                .any(|other| encloses(other))
+            {
+                // e.g. a tuple missing an element that was left out, which is not reported.
+                skipped.push(path.into());
+            } else {
+                skipped.push(path.into());
+                errors.push(error);
+            }
+        }
+
+        Err(Error::collected(errors))
+    }
+
+    /// Create the node of the entries nested under `segment`.
+    fn node(&self, value: Option<Value<'de>>, segment: &str) -> Node<'de> {
+        Node::nested(value, self.location.nested(segment), self.options)
+    }
+
+    /// Group entries by struct field.
+    ///
+    /// An entry whose key is exactly a field name provides the value of that field, an entry whose
+    /// key starts with a field name followed by the separator is nested under that field. Other
+    /// entries are left as-is so that they may be reported as unknown fields.
+    ///
+    /// If enabled, an entry whose key is a field name followed by the file suffix provides the
+    /// value of that field (unless another entry does) by naming the file to read it from.
+    fn group(mut self, fields: &'static [&'static str]) -> Result<Vec<(Key<'de>, Node<'de>)>> {
+        let mut nodes: Vec<(Key<'de>, Node<'de>)> = Vec::new();
+        let mut files: Vec<(&'static str, Key<'de>, Value<'de>)> = Vec::new();
+
+        for (key, value) in std::mem::take(&mut self.entries) {
+            if let Some(field) = fields.iter().copied().find(|field| key == **field) {
+                // Multiple values for the same field are left to `T` to report.
+                match nodes
+                    .iter_mut()
+                    .find(|(key, node)| *key == *field && !node.has_value())
+                {
+                    Some((_, node)) => node.set_value(value),
+                    None => nodes.push((key, self.node(Some(value), field))),
+                }
+
+                continue;
+            }
+
+            if let Some(suffix) = self.options.file_suffix.as_deref() {
+                let file = key.as_str().and_then(|key| key.strip_suffix(suffix));
+
+                if let Some(field) = fields.iter().copied().find(|field| file == Some(field)) {
+                    files.push((field, key, value));
+                    continue;
+                }
+            }
+
+            let nested = self.options.separator.as_deref().and_then(|separator| {
+                fields
+                    .iter()
+                    .copied()
+                    .filter(|field| {
+                        key.as_str()
+                            .and_then(|key| key.strip_prefix(field))
+                            .is_some_and(|rest| rest.starts_with(separator))
+                    })
+                    .max_by_key(|field| field.len())
+                    .map(|field| (field, field.len() + separator.len()))
+            });
+
+            match nested {
+                Some((field, len)) => {
+                    let key = key.split_off(len);
+
+                    match nodes.iter_mut().find(|(key, _)| *key == *field) {
+                        Some((_, node)) => node.push(key, value),
+                        None => {
+                            let mut node = self.node(None, field);
+                            node.push(key, value);
+                            nodes.push((Key::from(field), node));
+                        }
+                    }
+                }
+                None => {
+                    let node = self.node(Some(value), &key.to_string_lossy());
+                    nodes.push((key, node));
+                }
+            }
+        }
+
+        for (field, key, path) in files {
+            let position = match nodes.iter().position(|(key, _)| *key == *field) {
+                Some(position) => position,
+                None => {
+                    nodes.push((Key::from(field), self.node(None, field)));
+                    nodes.len() - 1
+                }
+            };
+
+            let node = &mut nodes[position].1;
+
+            // Files that could not be read in a previous attempt are not read again, see
+            // `Deserializer::deserialize`.
+            if node.has_value() || node.is_skipped() || node.is_substituted() {
+                continue;
+            }
+
+            let path = PathBuf::from(path.to_os_string());
+
+            match self.location.files.read(&path) {
+                Ok(contents) => node.set_value(Value::from(contents).with_options(self.options)),
+                Err(error) => {
+                    let key = self.location.nested(&key.to_string_lossy()).key;
+                    let field = self.location.nested(field).path;
+
+                    return Err(Error::read_file(path, error).with_context(key, field, None, false));
+                }
+            }
+        }
+
+        // Nodes that failed to deserialize in a previous attempt are left out, unless required,
+        // see `Deserializer::deserialize`.
+        nodes.retain(|(_, node)| !node.is_skipped());
+
+        for field in fields {
+            if nodes.iter().all(|(key, _)| *key != **field) {
+                let node = self.node(None, field);
+
+                if node.is_substituted() {
+                    nodes.push((Key::from(*field), node));
+                }
+            }
+        }
+
+        // Follow the field declaration order, so that e.g. the tag of adjacently tagged enums is
+        // visited before their content and the latter does not have to be buffered.
+        nodes.sort_by_key(|(key, _)| {
+            fields
+                .iter()
+                .position(|field| *key == **field)
+                .unwrap_or(fields.len())
+        });
+
+        Ok(nodes)
+    }
+
+    /// Group entries by sequence index.
+    ///
+    /// An entry whose key is exactly an index provides the value of that element, an entry whose
+    /// key starts with an index followed by the separator is nested under that element. Indices
+    /// must start at zero and be contiguous.
+    fn index(mut self) -> Result<Vec<Node<'de>>> {
+        let separator = self.options.separator.as_deref().unwrap_or_default();
+        let mut elements: BTreeMap<usize, Node<'de>> = BTreeMap::new();
+
+        for (key, value) in std::mem::take(&mut self.entries) {
+            let str = key.as_str().unwrap_or_default();
+
+            let (segment, nested) = match str.find(separator).filter(|_| !separator.is_empty()) {
+                Some(position) => (&str[..position], Some(position + separator.len())),
+                None => (str, None),
+            };
+
+            // Only accept canonical indices so that e.g. `1` and `01` are not merged.
+            let node = match segment.parse::<usize>() {
+                Ok(index) if index.to_string() == segment => elements
+                    .entry(index)
+                    .or_insert_with(|| self.node(None, segment)),
+                _ => return Err(Error::invalid_index(segment)),
+            };
+
+            match nested {
+                Some(len) => node.push(key.split_off(len), value),
+                None => node.set_value(value),
+            }
+        }
+
+        if let Some(missing) = elements
+            .keys()
+            .enumerate()
+            .find_map(|(expected, index)| (expected != *index).then_some(expected))
+        {
+            return Err(Error::missing_index(missing));
+        }
+
+        // See `Deserializer::deserialize`.
+        Ok(elements
+            .into_values()
+            .filter(|node| !node.is_skipped())
+            .collect())
+    }
+
+    /// Call `f`, giving context to the errors that `T` reports itself (i.e. missing and unknown
+    /// fields, and errors about values it buffered) as nodes cannot.
+    fn with_context<T>(self, f: impl FnOnce(Self) -> Result<T>) -> Result<T> {
+        let location = self.location.clone();
+        let redaction = &self.options.redaction;
+
+        // The value `T` reports an error about is unknown, so any redacted entry redacts it.
+        let redact = self.entries.iter().any(|(key, _)| {
+            redaction.applies(&format!("{}{}", location.key, key.to_string_lossy()))
+        });
+
+        f(self).map_err(|error| {
+            if error.key().is_some() {
+                return error;
+            }
+
+            let error = match error.field() {
+                Some(field) => {
+                    let location = location.nested(field);
+                    error.with_context(location.key, location.path, None, false)
+                }
+                None => error,
+            };
+
+            if redact {
+                error.redact()
+            } else {
+                error
+            }
+        })
+    }
+
+    /// Use every entry as a map entry, whatever its key.
+    fn entries(mut self) -> impl Iterator<Item = (Key<'de>, Node<'de>)> {
+        std::mem::take(&mut self.entries)
+            .into_iter()
+            .map(move |(key, value)| {
+                let node = self.node(Some(value), &key.to_string_lossy());
+                (key, node)
+            })
+            // See `Deserializer::deserialize`.
+            .filter(|(_, node)| !node.is_skipped())
     }
 }
 
-impl<'de, 'a, I> serde::de::Deserializer<'de> for &'a mut EnvDeserializer<'de, I>
-where
-    I: Iterator<Item = (Key<'de>, Value<'de>)>,
-{
+impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
     type Error = Error;
 
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_map(&mut self.0)
+        self.with_context(|deserializer| {
+            visitor.visit_map(MapDeserializer::new(
+                deserializer.group(fields)?.into_iter(),
+            ))
+        })
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.with_context(|deserializer| {
+            visitor.visit_map(MapDeserializer::new(deserializer.entries()))
+        })
+    }
+
+    // Required for internally tagged and untagged enums.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
     }
 
     fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
@@ -176,6 +679,6 @@ where
     util::unsupported_types! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
         bytes byte_buf option unit unit_struct tuple
-        any tuple_struct identifier enum map seq ignored_any
+        tuple_struct identifier enum seq ignored_any
     }
 }
@@ -0,0 +1,341 @@
+use serde::de::{
+    value::{MapDeserializer, SeqDeserializer},
+    IntoDeserializer,
+};
+
+use crate::{Error, Options, Result};
+
+use super::{
+    location::Location, placeholder::Placeholder, variant::Variant, Deserializer, Key, Value,
+};
+
+/// A struct field or sequence element, holding the variable named after it (if any) and the
+/// variables nested under it.
+pub struct Node<'de> {
+    value: Option<Value<'de>>,
+    children: Deserializer<'de>,
+    /// Unknown for nodes that are part of another (e.g. enum payloads), in which case errors get
+    /// the context of the enclosing node.
+    location: Option<Location>,
+}
+
+impl<'de> Node<'de> {
+    pub fn new(value: Option<Value<'de>>, options: &'de Options) -> Self {
+        Self {
+            value,
+            children: Deserializer::new(Vec::new(), Location::default(), options),
+            location: None,
+        }
+    }
+
+    pub fn nested(value: Option<Value<'de>>, location: Location, options: &'de Options) -> Self {
+        let children = Location {
+            key: format!(
+                "{}{}",
+                location.key,
+                options.separator.as_deref().unwrap_or_default()
+            ),
+            ..location.clone()
+        };
+
+        Self {
+            value,
+            children: Deserializer::new(Vec::new(), children, options),
+            location: Some(location),
+        }
+    }
+
+    pub fn has_value(&self) -> bool {
+        self.value.is_some()
+    }
+
+    pub fn set_value(&mut self, value: Value<'de>) {
+        self.value = Some(value);
+    }
+
+    pub fn push(&mut self, key: Key<'de>, value: Value<'de>) {
+        self.children.entries.push((key, value));
+    }
+
+    fn into_value(self) -> Result<Value<'de>> {
+        self.value.ok_or_else(Error::missing_value)
+    }
+
+    /// Whether this node should be deserialized from its indexed nested variables rather than its
+    /// delimited value.
+    fn is_indexed(&self) -> bool {
+        !self.children.entries.is_empty()
+    }
+
+    /// Whether this node failed to deserialize in a previous attempt, in which case it is left
+    /// out so that errors may be collected from the remaining nodes.
+    pub fn is_skipped(&self) -> bool {
+        self.location.as_ref().is_some_and(Location::is_skipped)
+    }
+
+    /// Whether this node was left out in a previous attempt but is required, in which case it is
+    /// replaced by a placeholder so that the fields declared after it may be checked.
+    pub fn is_substituted(&self) -> bool {
+        self.location.as_ref().is_some_and(Location::is_substituted)
+    }
+
+    /// Replace the references in the value of this node, if [interpolation] is enabled.
+    ///
+    /// Returns whether the value contains the value of a [redacted] variable.
+    ///
+    /// [interpolation]: crate#interpolation
+    /// [redacted]: crate::Options::redaction
+    fn interpolate(mut self, location: Option<&Location>) -> Result<(Self, bool)> {
+        let Some(location) = location.filter(|location| !location.is_substituted()) else {
+            return Ok((self, false));
+        };
+
+        let (Some(variables), Some(value)) = (&location.variables, &self.value) else {
+            return Ok((self, false));
+        };
+
+        let mut redact = false;
+
+        // Values that are not valid unicode are left as-is.
+        if let Some(str) = value.to_str().filter(|str| str.contains('$')) {
+            let (interpolated, resolved) = variables.interpolate(&location.key, str)?;
+            let redaction = &self.children.options.redaction;
+
+            redact = resolved.iter().any(|name| redaction.applies(name));
+            self.value = Some(value.with_str(interpolated));
+        }
+
+        Ok((self, redact))
+    }
+
+    /// Call `f`, giving the variable name, field path and value of this node as context to its
+    /// error.
+    fn with_context<T>(mut self, f: impl FnOnce(Self) -> Result<T>) -> Result<T> {
+        let options = self.children.options;
+        let location = self.location.take();
+        let mut value = self.value.as_ref().map(Value::to_os_string);
+        let mut redact = location
+            .as_ref()
+            .is_some_and(|location| options.redaction.applies(&location.key));
+
+        let result = self
+            .interpolate(location.as_ref())
+            .and_then(|(node, redacted)| {
+                // Errors past this point are about the interpolated value.
+                value = node.value.as_ref().map(Value::to_os_string);
+                redact |= redacted;
+                f(node)
+            });
+
+        result.map_err(|error| match (location, value) {
+            (Some(location), value) => {
+                error.with_context(location.key, location.path, value, redact)
+            }
+            // The enclosing node gives the context, but may not know this value.
+            (None, Some(value)) => error.with_value(value),
+            (None, None) => error,
+        })
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Node<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> serde::de::Deserializer<'de> for Node<'de> {
+    type Error = Error;
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.is_substituted() {
+            return self.with_context(|_| {
+                serde::de::Deserializer::deserialize_struct(Placeholder, name, fields, visitor)
+            });
+        }
+
+        self.with_context(|node| {
+            if node.children.options.separator.is_none() {
+                return node.into_value()?.deserialize_struct(name, fields, visitor);
+            }
+
+            if node.has_value() {
+                return Err(Error::unexpected_value());
+            }
+
+            node.children.deserialize_struct(name, fields, visitor)
+        })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.is_substituted() {
+            return self.with_context(|_| {
+                serde::de::Deserializer::deserialize_enum(Placeholder, name, variants, visitor)
+            });
+        }
+
+        self.with_context(|node| {
+            let options = node.children.options;
+            let (name, payload) = match node.value {
+                Some(value) => value.into_variant(variants)?,
+                None => return Err(Error::missing_value()),
+            };
+
+            // The payload is either part of the value or nested under the node.
+            let payload = match payload {
+                Some(payload) => Node::new(Some(payload), options),
+                None => Node {
+                    value: None,
+                    children: node.children,
+                    location: None,
+                },
+            };
+
+            visitor.visit_enum(Variant::new(name, payload))
+        })
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.is_substituted() {
+            return self
+                .with_context(|_| serde::de::Deserializer::deserialize_seq(Placeholder, visitor));
+        }
+
+        self.with_context(|node| {
+            if !node.is_indexed() {
+                return node.into_value()?.deserialize_seq(visitor);
+            }
+
+            let mut deserializer = SeqDeserializer::new(node.children.index()?.into_iter());
+            let value = visitor.visit_seq(&mut deserializer)?;
+            deserializer.end()?;
+            Ok(value)
+        })
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.is_substituted() {
+            return self.with_context(|_| {
+                serde::de::Deserializer::deserialize_tuple(Placeholder, len, visitor)
+            });
+        }
+
+        if !self.is_indexed() {
+            return self.with_context(|node| node.into_value()?.deserialize_tuple(len, visitor));
+        }
+
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.is_substituted() {
+            return self
+                .with_context(|_| serde::de::Deserializer::deserialize_any(Placeholder, visitor));
+        }
+
+        if self.is_indexed() {
+            return self.deserialize_map(visitor);
+        }
+
+        self.with_context(|node| node.into_value()?.deserialize_any(visitor))
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.is_substituted() {
+            return self
+                .with_context(|_| serde::de::Deserializer::deserialize_map(Placeholder, visitor));
+        }
+
+        self.with_context(|node| {
+            if node.children.options.separator.is_none() {
+                return node.into_value()?.deserialize_map(visitor);
+            }
+
+            if node.has_value() {
+                return Err(Error::unexpected_value());
+            }
+
+            visitor.visit_map(MapDeserializer::new(node.children.entries()))
+        })
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.is_substituted() {
+            return self.with_context(|_| {
+                serde::de::Deserializer::deserialize_option(Placeholder, visitor)
+            });
+        }
+
+        // An empty enum payload (`VARIANT:`, or `VARIANT` without nested variables) is `None`, as
+        // it is serialized, whereas a field is `None` if it has no variable.
+        let is_empty = match &self.value {
+            Some(value) => value.is_empty(),
+            None => !self.is_indexed(),
+        };
+
+        if self.location.is_none() && is_empty {
+            return visitor.visit_none();
+        }
+
+        visitor.visit_some(self)
+    }
+
+    // See `Placeholder::is_human_readable`.
+    fn is_human_readable(&self) -> bool {
+        !self.is_substituted()
+    }
+
+    // Required for skipping fields when visiting maps.
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    crate::de::util::forward_to_value! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct
+        tuple_struct identifier
+    }
+}
@@ -0,0 +1,278 @@
+use serde::de::{
+    value::{MapDeserializer, SeqDeserializer, StrDeserializer},
+    IntoDeserializer,
+};
+
+use crate::{Error, Result};
+
+/// Implement `deserialize_<ty>` by calling `deserialize_<target>`.
+macro_rules! forward_to {
+    ($($target:ident: $($ty:ident)*;)*) => {
+        $($(
+            paste::paste! {
+                fn [<deserialize_ $ty>]<V>(self, visitor: V) -> Result<V::Value>
+                where
+                    V: serde::de::Visitor<'de>,
+                {
+                    self.[<deserialize_ $target>](visitor)
+                }
+            }
+        )*)*
+    };
+}
+
+/// Deserialize an arbitrary (i.e. one, empty or first) value of any type, standing in for a
+/// required node that failed to deserialize so that the fields declared after it may be checked.
+#[derive(Clone, Copy)]
+pub struct Placeholder;
+
+impl<'de> IntoDeserializer<'de, Error> for Placeholder {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> serde::de::Deserializer<'de> for Placeholder {
+    type Error = Error;
+
+    // e.g. `IpAddr` is deserialized from bytes, any of which are valid, rather than a string.
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        // One rather than zero, which e.g. `NonZeroI64` rejects.
+        visitor.visit_i64(1)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_u64(1)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_char('\0')
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_bytes(&[])
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_tuple(0, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDeserializer::new(std::iter::repeat_n(self, len)))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_struct("", &[], visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_map(MapDeserializer::new(
+            fields.iter().map(|field| (*field, self)),
+        ))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_enum(PlaceholderVariant(
+            variants.first().copied().unwrap_or_default(),
+        ))
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        unit unit_struct identifier
+    }
+
+    forward_to! {
+        i64: i8 i16 i32 i128;
+        u64: u8 u16 u32 u128;
+        f64: f32;
+        str: string;
+        bytes: byte_buf;
+    }
+}
+
+/// The first variant of an enum, with a placeholder payload.
+struct PlaceholderVariant(&'static str);
+
+impl<'de> serde::de::EnumAccess<'de> for PlaceholderVariant {
+    type Error = Error;
+    type Variant = Placeholder;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let variant: StrDeserializer<Error> = self.0.into_deserializer();
+        Ok((seed.deserialize(variant)?, Placeholder))
+    }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for Placeholder {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        serde::de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        serde::de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::Placeholder;
+
+    #[test]
+    fn deserialize() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        enum Level {
+            Info(u8),
+            Debug,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Test {
+            a: u8,
+            b: String,
+            c: Option<bool>,
+            d: Vec<f32>,
+            e: (i64, char),
+            f: Level,
+        }
+
+        assert_eq!(
+            Test::deserialize(Placeholder).unwrap(),
+            Test {
+                a: 1,
+                b: String::new(),
+                c: None,
+                d: Vec::new(),
+                e: (1, '\0'),
+                f: Level::Info(1),
+            }
+        );
+    }
+}
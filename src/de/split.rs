@@ -0,0 +1,160 @@
+use std::borrow::Cow;
+
+use crate::{Error, Result};
+
+/// Split `value` on `delimiter`, see [sequences] for the escaping rules.
+///
+/// [sequences]: crate#sequences
+pub fn split<'a>(value: &'a str, delimiter: &str) -> Result<Vec<Cow<'a, str>>> {
+    let mut elements = Vec::new();
+
+    if value.is_empty() {
+        return Ok(elements);
+    }
+
+    let mut start = 0;
+
+    loop {
+        let (element, end) = if value[start..].starts_with('"') {
+            quoted(value, start, delimiter)?
+        } else {
+            unquoted(value, start, delimiter)
+        };
+
+        elements.push(element);
+
+        if end == value.len() {
+            return Ok(elements);
+        }
+
+        start = end + delimiter.len();
+    }
+}
+
+/// Parse the unquoted element starting at `start`, returning it along with the index of the
+/// delimiter that ends it (or the length of `value`).
+fn unquoted<'a>(value: &'a str, start: usize, delimiter: &str) -> (Cow<'a, str>, usize) {
+    let mut owned: Option<String> = None;
+    let mut chunk = start;
+    let mut index = start;
+
+    while let Some(rest) = value.get(index..).filter(|rest| !rest.is_empty()) {
+        if rest.starts_with(delimiter) {
+            break;
+        }
+
+        if let Some(escaped) = escape(rest, delimiter) {
+            let owned = owned.get_or_insert_with(String::new);
+            owned.push_str(&value[chunk..index]);
+            owned.push_str(escaped);
+            index += '\\'.len_utf8() + escaped.len();
+            chunk = index;
+            continue;
+        }
+
+        index += rest.chars().next().map_or(1, char::len_utf8);
+    }
+
+    let element = match owned {
+        Some(mut owned) => {
+            owned.push_str(&value[chunk..index]);
+            Cow::Owned(owned)
+        }
+        None => Cow::Borrowed(&value[start..index]),
+    };
+
+    (element, index)
+}
+
+/// Parse the quoted element starting at `start`, returning it along with the index following the
+/// closing quote.
+fn quoted<'a>(value: &'a str, start: usize, delimiter: &str) -> Result<(Cow<'a, str>, usize)> {
+    let mut element = String::new();
+    let mut index = start + '"'.len_utf8();
+
+    while let Some(rest) = value.get(index..).filter(|rest| !rest.is_empty()) {
+        if let Some(escaped) = escape(rest, delimiter) {
+            element.push_str(escaped);
+            index += '\\'.len_utf8() + escaped.len();
+            continue;
+        }
+
+        if rest.starts_with('"') {
+            let end = index + '"'.len_utf8();
+
+            if end == value.len() || value[end..].starts_with(delimiter) {
+                return Ok((Cow::Owned(element), end));
+            }
+
+            return Err(Error::invalid_sequence(
+                end,
+                "expected a delimiter after the closing quote",
+            ));
+        }
+
+        let char = rest.chars().next().unwrap_or_default();
+        element.push(char);
+        index += char.len_utf8();
+    }
+
+    Err(Error::invalid_sequence(start, "unterminated quote"))
+}
+
+/// If `rest` starts with an escape sequence, return the escaped text.
+///
+/// Only backslashes, double quotes and the delimiter can be escaped, any other backslash is kept
+/// as-is.
+fn escape<'a>(rest: &str, delimiter: &'a str) -> Option<&'a str> {
+    let rest = rest.strip_prefix('\\')?;
+
+    if rest.starts_with('\\') {
+        Some("\\")
+    } else if rest.starts_with('"') {
+        Some("\"")
+    } else if rest.starts_with(delimiter) {
+        Some(delimiter)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split;
+
+    #[test]
+    fn plain() {
+        assert!(split("", ",").unwrap().is_empty());
+        assert_eq!(split("a", ",").unwrap(), ["a"]);
+        assert_eq!(split("a,,b,", ",").unwrap(), ["a", "", "b", ""]);
+        assert_eq!(split("a::b", "::").unwrap(), ["a", "b"]);
+    }
+
+    #[test]
+    fn escaped() {
+        assert_eq!(split(r"a\,b,c", ",").unwrap(), ["a,b", "c"]);
+        assert_eq!(split(r"a\\,b", ",").unwrap(), [r"a\", "b"]);
+        assert_eq!(split(r#"\"a"#, ",").unwrap(), [r#""a"#]);
+        assert_eq!(split(r"^\d+$,\", ",").unwrap(), [r"^\d+$", r"\"]);
+        assert_eq!(split(r"a\::b::c", "::").unwrap(), ["a::b", "c"]);
+    }
+
+    #[test]
+    fn quoted() {
+        assert_eq!(split(r#""a,b",c,"""#, ",").unwrap(), ["a,b", "c", ""]);
+        assert_eq!(split(r#""a\"b\\",c"#, ",").unwrap(), [r#"a"b\"#, "c"]);
+        assert_eq!(split(r#"a"b,c"#, ",").unwrap(), [r#"a"b"#, "c"]);
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(
+            split(r#"a,"b,c"#, ",").unwrap_err().to_string(),
+            "invalid sequence at position 2: unterminated quote"
+        );
+        assert_eq!(
+            split(r#""a"b,c"#, ",").unwrap_err().to_string(),
+            "invalid sequence at position 3: expected a delimiter after the closing quote"
+        );
+    }
+}
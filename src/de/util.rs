@@ -45,5 +45,57 @@ macro_rules! unsupported_types_helper {
     }
 }
 
+macro_rules! forward_to_value {
+    ($($ty:ident)*) => {
+        $($crate::de::util::forward_to_value_helper!{$ty})*
+    }
+}
+
+macro_rules! forward_to_value_helper {
+    (tuple) => {
+        $crate::de::util::forward_to_value_helper!{
+            tuple(len: usize)
+        }
+    };
+    (enum) => {
+        $crate::de::util::forward_to_value_helper!{
+            enum(name: &'static str, variants: &'static [&'static str])
+        }
+    };
+    (tuple_struct) => {
+        $crate::de::util::forward_to_value_helper!{
+            tuple_struct(name: &'static str, len: usize)
+        }
+    };
+    (unit_struct) => {
+        $crate::de::util::forward_to_value_helper!{
+            unit_struct(name: &'static str)
+        }
+    };
+    ($ty:ident) => {
+        $crate::de::util::forward_to_value_helper!{$ty()}
+    };
+    ($ty:ident($($arg:ident : $arg_ty:ty),*)) => {
+        paste::paste! {
+            fn [<deserialize_ $ty>]<V>(self, $($arg: $arg_ty,)* visitor: V) -> Result<V::Value>
+            where
+                V: serde::de::Visitor<'de>
+            {
+                if self.is_substituted() {
+                    return self.with_context(|_| serde::de::Deserializer::[<deserialize_ $ty>](
+                        $crate::de::placeholder::Placeholder,
+                        $($arg,)*
+                        visitor,
+                    ));
+                }
+
+                self.with_context(|node| node.into_value()?.[<deserialize_ $ty>]($($arg,)* visitor))
+            }
+        }
+    }
+}
+
+pub(crate) use forward_to_value;
+pub(crate) use forward_to_value_helper;
 pub(crate) use unsupported_types;
 pub(crate) use unsupported_types_helper;
@@ -3,48 +3,148 @@ use std::{
     ffi::{OsStr, OsString},
 };
 
-use serde::de::IntoDeserializer;
+use serde::de::{value::SeqDeserializer, IntoDeserializer};
 
-use crate::{Error, Result};
+use crate::{options::DEFAULT_OPTIONS, Error, InferredType, Options, Result};
 
-pub struct Value<'de>(Cow<'de, OsStr>);
+use super::{node::Node, split::split, variant::Variant};
+
+#[derive(Clone)]
+pub struct Value<'de> {
+    inner: Cow<'de, OsStr>,
+    options: &'de Options,
+}
+
+impl<'de> Value<'de> {
+    pub fn with_options(self, options: &'de Options) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Replace the contents of this value, keeping its options.
+    pub fn with_str(&self, value: String) -> Self {
+        Self::from(value).with_options(self.options)
+    }
+
+    pub fn to_str(&self) -> Option<&str> {
+        self.inner.to_str()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn to_os_string(&self) -> OsString {
+        self.inner.clone().into_owned()
+    }
+
+    /// Split the value on the [delimiter](Options::delimiter), an empty value yields no elements.
+    ///
+    /// See [sequences](crate#sequences) for the escaping rules.
+    fn split(self) -> Result<Vec<Value<'de>>> {
+        let options = self.options;
+        let delimiter = options.delimiter.as_ref();
+
+        let elements: Vec<Value<'de>> = match self.inner {
+            Cow::Borrowed(value) => match value.to_str() {
+                Some(str) => split(str, delimiter)?
+                    .into_iter()
+                    .map(Value::from)
+                    .collect(),
+                None => return Err(Error::invalid_unicode(value.to_owned())),
+            },
+            Cow::Owned(value) => split(
+                &value.into_string().map_err(Error::invalid_unicode)?,
+                delimiter,
+            )?
+            .into_iter()
+            .map(|element| Value::from(element.into_owned()))
+            .collect(),
+        };
+
+        Ok(elements
+            .into_iter()
+            .map(|element| element.with_options(options))
+            .collect())
+    }
+
+    /// Split a `VARIANT:payload` value into the variant name and its payload, unless the whole
+    /// value is a variant name.
+    pub fn into_variant(self, variants: &[&str]) -> Result<(Value<'de>, Option<Value<'de>>)> {
+        let options = self.options;
+
+        let Some(str) = self.inner.to_str() else {
+            return Err(Error::invalid_unicode(self.inner.into_owned()));
+        };
+
+        let position = match str.find(':') {
+            Some(position) if !variants.contains(&str) && variants.contains(&&str[..position]) => {
+                position
+            }
+            _ => return Ok((self, None)),
+        };
+
+        let (name, payload) = match self.inner {
+            Cow::Borrowed(value) => {
+                let str = value.to_str().unwrap_or_default();
+                (
+                    Value::from(&str[..position]),
+                    Value::from(&str[position + 1..]),
+                )
+            }
+            Cow::Owned(value) => {
+                let mut name = value.into_string().unwrap_or_default();
+                let payload = name.split_off(position + 1);
+                name.truncate(position);
+                (Value::from(name), Value::from(payload))
+            }
+        };
+
+        Ok((
+            name.with_options(options),
+            Some(payload.with_options(options)),
+        ))
+    }
+}
 
 impl<'de> From<Cow<'de, OsStr>> for Value<'de> {
     fn from(value: Cow<'de, OsStr>) -> Self {
-        Self(value)
+        Self {
+            inner: value,
+            options: &DEFAULT_OPTIONS,
+        }
     }
 }
 
 impl<'de> From<&'de OsStr> for Value<'de> {
     fn from(value: &'de OsStr) -> Self {
-        Self(Cow::Borrowed(value))
+        Self::from(Cow::Borrowed(value))
     }
 }
 
 impl<'de> From<OsString> for Value<'de> {
     fn from(value: OsString) -> Self {
-        Self(Cow::Owned(value))
+        Self::from(Cow::<OsStr>::Owned(value))
     }
 }
 
 impl<'de> From<Cow<'de, str>> for Value<'de> {
     fn from(value: Cow<'de, str>) -> Self {
         match value {
-            Cow::Owned(string) => Self(Cow::Owned(OsString::from(string))),
-            Cow::Borrowed(str) => Self(Cow::Borrowed(OsStr::new(str))),
+            Cow::Owned(string) => Self::from(OsString::from(string)),
+            Cow::Borrowed(str) => Self::from(OsStr::new(str)),
         }
     }
 }
 
 impl<'de> From<&'de str> for Value<'de> {
     fn from(value: &'de str) -> Self {
-        Self(Cow::Borrowed(OsStr::new(value)))
+        Self::from(OsStr::new(value))
     }
 }
 
 impl<'de> From<String> for Value<'de> {
     fn from(value: String) -> Self {
-        Self(Cow::Owned(OsString::from(value)))
+        Self::from(OsString::from(value))
     }
 }
 
@@ -64,9 +164,9 @@ macro_rules! validate_unicode_and_parse {
                 where
                     V: serde::de::Visitor<'de>
                 {
-                    match self.0.to_str() {
+                    match self.inner.to_str() {
                         Some(str) => visitor.[<visit_ $ty>](str.parse::<$ty>()?),
-                        None => Err(Error::invalid_unicode(self.0.into_owned())),
+                        None => Err(Error::invalid_unicode(self.inner.into_owned())),
                     }
                 }
             )*
@@ -81,9 +181,9 @@ impl<'de> serde::de::Deserializer<'de> for Value<'de> {
     where
         V: serde::de::Visitor<'de>,
     {
-        match self.0.to_str() {
+        match self.inner.to_str() {
             Some(str) => visitor.visit_str(str),
-            None => Err(Error::invalid_unicode(self.0.into_owned())),
+            None => Err(Error::invalid_unicode(self.inner.into_owned())),
         }
     }
 
@@ -92,13 +192,63 @@ impl<'de> serde::de::Deserializer<'de> for Value<'de> {
         V: serde::de::Visitor<'de>,
     {
         visitor.visit_string(
-            self.0
+            self.inner
                 .into_owned()
                 .into_string()
                 .map_err(Error::invalid_unicode)?,
         )
     }
 
+    // Values are untyped, they are provided to self-describing types (e.g. `#[serde(untagged)]`
+    // enums or the content buffered by `#[serde(flatten)]`) as the first inferred type that
+    // matches, or as strings.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let Some(str) = self.inner.to_str() else {
+            return Err(Error::invalid_unicode(self.inner.into_owned()));
+        };
+
+        for ty in self.options.inference.iter() {
+            match ty {
+                InferredType::Bool => {
+                    if str.eq_ignore_ascii_case("true") {
+                        return visitor.visit_bool(true);
+                    }
+
+                    if str.eq_ignore_ascii_case("false") {
+                        return visitor.visit_bool(false);
+                    }
+                }
+                InferredType::Integer => {
+                    if let Ok(value) = str.parse::<u64>() {
+                        return visitor.visit_u64(value);
+                    }
+
+                    if let Ok(value) = str.parse::<i64>() {
+                        return visitor.visit_i64(value);
+                    }
+
+                    if let Ok(value) = str.parse::<u128>() {
+                        return visitor.visit_u128(value);
+                    }
+
+                    if let Ok(value) = str.parse::<i128>() {
+                        return visitor.visit_i128(value);
+                    }
+                }
+                InferredType::Float => {
+                    if let Some(value) = str.parse::<f64>().ok().filter(|value| value.is_finite()) {
+                        return visitor.visit_f64(value);
+                    }
+                }
+            }
+        }
+
+        self.deserialize_string(visitor)
+    }
+
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
@@ -110,38 +260,42 @@ impl<'de> serde::de::Deserializer<'de> for Value<'de> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let lowercase_input = self.0.to_str().map(str::to_lowercase);
-
-        #[cfg(feature = "truthy-falsy")]
-        match lowercase_input.as_deref() {
-            Some("true" | "t" | "yes" | "y" | "on" | "1") => visitor.visit_bool(true),
-            Some("false" | "f" | "no" | "n" | "off" | "0") => visitor.visit_bool(false),
-            _ => Err(Error::invalid_bool(self.0.into_owned())),
-        }
-
-        #[cfg(not(feature = "truthy-falsy"))]
-        match lowercase_input.as_deref() {
-            Some("true") => visitor.visit_bool(true),
-            Some("false") => visitor.visit_bool(false),
-            _ => Err(Error::invalid_bool(self.0.into_owned())),
+        let lowercase_input = self.inner.to_str().map(str::to_lowercase);
+        let matches = |values: &[Cow<'static, str>]| {
+            lowercase_input
+                .as_deref()
+                .is_some_and(|input| values.iter().any(|value| value.to_lowercase() == input))
+        };
+
+        if matches(&self.options.truthy) {
+            visitor.visit_bool(true)
+        } else if matches(&self.options.falsy) {
+            visitor.visit_bool(false)
+        } else {
+            Err(Error::invalid_bool(self.inner.into_owned()))
         }
     }
 
     fn deserialize_enum<V>(
         self,
-        name: &'static str,
+        _name: &'static str,
         variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
     {
-        match self.0.to_str() {
-            Some(str) => str
-                .into_deserializer()
-                .deserialize_enum(name, variants, visitor),
-            None => Err(Error::invalid_unicode(self.0.into_owned())),
-        }
+        let options = self.options;
+        let (name, payload) = self.into_variant(variants)?;
+
+        visitor.visit_enum(Variant::new(name, Node::new(payload, options)))
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
@@ -151,6 +305,23 @@ impl<'de> serde::de::Deserializer<'de> for Value<'de> {
         visitor.visit_newtype_struct(self)
     }
 
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let mut deserializer = SeqDeserializer::new(self.split()?.into_iter());
+        let value = visitor.visit_seq(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: serde::de::Visitor<'de>,
@@ -171,8 +342,8 @@ impl<'de> serde::de::Deserializer<'de> for Value<'de> {
     }
 
     crate::de::util::unsupported_types! {
-        bytes byte_buf unit unit_struct seq tuple
-        tuple_struct map struct identifier any
+        bytes byte_buf unit unit_struct
+        tuple_struct map struct
     }
 }
 
@@ -211,8 +382,36 @@ mod tests {
         assert!(bool::deserialize(Value::from("gibberish")).is_err());
     }
 
+    #[test]
+    fn deserialize_bool_vocabulary() {
+        let parse = |options: &crate::Options, value| {
+            bool::deserialize(Value::from(value).with_options(options)).ok()
+        };
+
+        let options = crate::Options::new().strict_bools();
+
+        assert_eq!(parse(&options, "TRUE"), Some(true));
+        assert_eq!(parse(&options, "false"), Some(false));
+        assert_eq!(parse(&options, "yes"), None);
+        assert_eq!(parse(&options, "0"), None);
+
+        let options = crate::Options::new().lenient_bools();
+
+        assert_eq!(parse(&options, "yes"), Some(true));
+        assert_eq!(parse(&options, "0"), Some(false));
+
+        let options = crate::Options::new()
+            .truthy(["Enabled"])
+            .falsy([String::from("disabled")]);
+
+        assert_eq!(parse(&options, "ENABLED"), Some(true));
+        assert_eq!(parse(&options, "disabled"), Some(false));
+        assert_eq!(parse(&options, "true"), None);
+    }
+
     #[test]
     fn deserialize_enum() {
+        #[allow(dead_code)]
         #[derive(serde::Deserialize, Debug)]
         #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
         enum Switch {
@@ -232,11 +431,95 @@ mod tests {
             Ok(Switch::Off)
         ));
 
+        assert!(matches!(
+            Switch::deserialize(Value::from("NEW_TYPE_VARIANT:true")),
+            Ok(Switch::NewTypeVariant(true))
+        ));
+
+        assert!(matches!(
+            Switch::deserialize(Value::from(String::from("NEW_TYPE_VARIANT:false"))),
+            Ok(Switch::NewTypeVariant(false))
+        ));
+
         assert!(Switch::deserialize(Value::from("NEW_TYPE_VARIANT")).is_err());
+        assert!(Switch::deserialize(Value::from("NEW_TYPE_VARIANT:gibberish")).is_err());
         assert!(Switch::deserialize(Value::from("STRUCT_VARIANT")).is_err());
+        assert!(Switch::deserialize(Value::from("ON:gibberish")).is_err());
         assert!(Switch::deserialize(Value::from("gibberish")).is_err());
     }
 
+    #[test]
+    fn deserialize_seq() {
+        use std::collections::BTreeSet;
+
+        assert_eq!(
+            Vec::<u16>::deserialize(Value::from("1,2,3")).unwrap(),
+            [1, 2, 3]
+        );
+
+        assert_eq!(
+            Vec::<String>::deserialize(Value::from(String::from("a,,b"))).unwrap(),
+            ["a", "", "b"]
+        );
+
+        assert_eq!(
+            BTreeSet::<bool>::deserialize(Value::from("true,false,true")).unwrap(),
+            BTreeSet::from([false, true])
+        );
+
+        assert_eq!(<[u8; 2]>::deserialize(Value::from("1,2")).unwrap(), [1, 2]);
+        assert!(<[u8; 2]>::deserialize(Value::from("1,2,3")).is_err());
+        assert!(<[u8; 2]>::deserialize(Value::from("1")).is_err());
+
+        assert!(Vec::<u8>::deserialize(Value::from("")).unwrap().is_empty());
+        assert!(Vec::<u8>::deserialize(Value::from("1,a")).is_err());
+    }
+
+    #[test]
+    fn deserialize_seq_delimiter() {
+        let options = crate::Options::new().delimiter(";");
+
+        assert_eq!(
+            Vec::<String>::deserialize(Value::from("a,b;c").with_options(&options)).unwrap(),
+            ["a,b", "c"]
+        );
+    }
+
+    #[test]
+    fn deserialize_any() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        #[serde(untagged)]
+        enum Any {
+            Bool(bool),
+            Integer(i64),
+            Float(f64),
+            String(String),
+        }
+
+        let options = crate::Options::new().infer_types();
+        let any = |value| Any::deserialize(Value::from(value).with_options(&options)).unwrap();
+
+        assert_eq!(any("TRUE"), Any::Bool(true));
+        assert_eq!(any("false"), Any::Bool(false));
+        assert_eq!(any("1"), Any::Integer(1));
+        assert_eq!(any("-1"), Any::Integer(-1));
+        assert_eq!(any("1.5"), Any::Float(1.5));
+        assert_eq!(any("inf"), Any::String("inf".into()));
+        assert_eq!(any("yes"), Any::String("yes".into()));
+
+        let options = crate::Options::new().inference_order(&[crate::InferredType::Float][..]);
+
+        assert_eq!(
+            Any::deserialize(Value::from("1").with_options(&options)).unwrap(),
+            Any::Float(1.0)
+        );
+
+        assert_eq!(
+            Any::deserialize(Value::from("1")).unwrap(),
+            Any::String("1".into())
+        );
+    }
+
     #[test]
     fn deserialize_newtype_struct() {
         #[derive(serde::Deserialize, Debug, PartialEq)]
@@ -0,0 +1,69 @@
+use crate::{Error, Result};
+
+use super::{node::Node, value::Value};
+
+/// An enum variant, identified by its name and holding its payload (if any).
+pub struct Variant<'de> {
+    name: Value<'de>,
+    payload: Node<'de>,
+}
+
+impl<'de> Variant<'de> {
+    pub fn new(name: Value<'de>, payload: Node<'de>) -> Self {
+        Self { name, payload }
+    }
+}
+
+impl<'de> serde::de::EnumAccess<'de> for Variant<'de> {
+    type Error = Error;
+    type Variant = Node<'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let name = self.name.to_os_string();
+        let variant = seed
+            .deserialize(self.name)
+            .map_err(|error| error.with_value(name))?;
+
+        Ok((variant, self.payload))
+    }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for Node<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        // Nested variables are ignored as they may be meant for other variants.
+        if self.has_value() {
+            return Err(serde::de::Error::invalid_type(
+                serde::de::Unexpected::NewtypeVariant,
+                &"unit variant",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        serde::de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        serde::de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
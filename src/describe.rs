@@ -0,0 +1,699 @@
+use std::{cell::RefCell, cmp::Reverse};
+
+use serde::{
+    de::{value::StrDeserializer, IntoDeserializer},
+    Deserialize,
+};
+
+use crate::{
+    de::{Location, Placeholder},
+    options::DEFAULT_OPTIONS,
+    Error, Options, Result,
+};
+
+/// Describe the variables `T` is deserialized from, see [describing types].
+///
+/// # Example
+/// ```rust
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// enum Level {
+///     Debug,
+///     Info,
+/// }
+///
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     timeout: u16,
+///     hosts: Vec<String>,
+///     level: Option<Level>,
+/// }
+///
+/// let description = de_env::describe::<Config>();
+///
+/// assert_eq!(
+///     description.to_markdown(),
+///     "\
+/// | Variable | Type | Required | Values |
+/// |---|---|---|---|
+/// | `TIMEOUT` | `u16` | yes | |
+/// | `HOSTS` | list of `string` | yes | |
+/// | `LEVEL` | `enum` | no | `DEBUG`, `INFO` |
+/// "
+/// );
+/// ```
+///
+/// [describing types]: crate#describing-types
+#[must_use]
+pub fn describe<'de, T>() -> Description
+where
+    T: Deserialize<'de>,
+{
+    DEFAULT_OPTIONS.describe::<T>()
+}
+
+/// The variables a type is deserialized from, see [`describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Description {
+    variables: Vec<Variable>,
+}
+
+/// A variable read by a type, see [`describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    key: String,
+    path: String,
+    ty: String,
+    required: bool,
+    variants: Vec<&'static str>,
+}
+
+impl Description {
+    /// Trace the deserialization of `T`.
+    ///
+    /// The tracing stops at the first type that rejects its sample value (e.g. an `IpAddr`
+    /// given an empty string), so `T` is deserialized again with the fields that failed moved
+    /// last (the most recent failure first), as long as new variables or failures are found.
+    pub(crate) fn new<'de, T>(options: &Options) -> Self
+    where
+        T: Deserialize<'de>,
+    {
+        let variables = RefCell::new(Vec::new());
+        let mut failed: Vec<String> = Vec::new();
+
+        loop {
+            let tracer = Tracer {
+                options,
+                location: None,
+                children: Location {
+                    key: options.prefix.as_deref().unwrap_or_default().to_owned(),
+                    ..Location::default()
+                },
+                optional: false,
+                ancestors: Vec::new(),
+                failed: &failed,
+                variables: &variables,
+            };
+
+            let known = variables.borrow().len();
+
+            let Err(error) = T::deserialize(tracer) else {
+                break;
+            };
+
+            match error.path() {
+                Some(path) if !failed.iter().any(|failed| failed == path) => {
+                    failed.push(path.to_owned());
+                }
+                _ if variables.borrow().len() > known => {}
+                _ => break,
+            }
+        }
+
+        Self {
+            variables: variables.into_inner(),
+        }
+    }
+
+    /// The variables, in field declaration order.
+    #[must_use]
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+
+    /// Render a Markdown table of the variables, with their type, whether they are required and
+    /// the variants of enums.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut output =
+            String::from("| Variable | Type | Required | Values |\n|---|---|---|---|\n");
+
+        for variable in &self.variables {
+            let ty = match variable.ty.strip_prefix("list of ") {
+                Some(element) => format!("list of `{element}`"),
+                None => format!("`{}`", variable.ty),
+            };
+
+            let values: Vec<String> = variable
+                .variants
+                .iter()
+                .map(|variant| format!("`{variant}`"))
+                .collect();
+
+            output.push_str(&format!(
+                "| `{}` | {ty} | {} | {}|\n",
+                variable.key,
+                if variable.required { "yes" } else { "no" },
+                match values.as_slice() {
+                    [] => String::new(),
+                    values => format!("{} ", values.join(", ")),
+                },
+            ));
+        }
+
+        output
+    }
+
+    /// Render a `.env.example` template, where each variable is preceded by a comment giving its
+    /// type.
+    ///
+    /// Optional variables and those matching a pattern (e.g. the entries of a map, `LABELS_*`)
+    /// are commented out.
+    #[must_use]
+    pub fn to_env_example(&self) -> String {
+        let mut output = String::new();
+
+        for variable in &self.variables {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+
+            output.push_str(&format!("# {}", variable.ty));
+
+            if !variable.variants.is_empty() {
+                output.push_str(&format!(", one of {}", variable.variants.join(", ")));
+            }
+
+            if !variable.required {
+                output.push_str(" (optional)");
+            }
+
+            output.push('\n');
+
+            if !variable.required || variable.is_pattern() {
+                output.push_str("# ");
+            }
+
+            output.push_str(&variable.key);
+            output.push_str("=\n");
+        }
+
+        output
+    }
+}
+
+impl Variable {
+    /// The variable name, with the prefix and separators applied.
+    ///
+    /// The entries of maps are read from every variable nested under the field, which is
+    /// represented by a `*` (e.g. `LABELS_*`). Sequences of structs are represented by their first
+    /// element (e.g. `SERVERS_0_HOST`).
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The field path, segments are separated by dots.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The type of the value, e.g. `u16`, `bool`, `string`, `enum` or `list of u16`.
+    ///
+    /// Types that deserialize from a string (e.g. `IpAddr`) are reported as `string`. Types that
+    /// cannot be deserialized (e.g. a map without a separator) or traced (e.g. flattened,
+    /// internally tagged or recursive types) are reported as `unsupported`.
+    #[must_use]
+    pub fn ty(&self) -> &str {
+        &self.ty
+    }
+
+    /// Whether the variable must be set, i.e. it is not an `Option` nor nested under one.
+    ///
+    /// Fields with a `#[serde(default)]` attribute are reported as required.
+    #[must_use]
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// The variants of an enum (or of the elements of a sequence of enums).
+    #[must_use]
+    pub fn variants(&self) -> &[&'static str] {
+        &self.variants
+    }
+
+    fn is_pattern(&self) -> bool {
+        self.key.contains('*')
+    }
+}
+
+/// Implement `deserialize_<ty>` by recording a variable of type `name` and visiting a sample
+/// value.
+macro_rules! trace {
+    ($($ty:ident($name:literal) => $visit:ident($sample:expr);)*) => {
+        $(
+            paste::paste! {
+                fn [<deserialize_ $ty>]<V>(self, visitor: V) -> Result<V::Value>
+                where
+                    V: serde::de::Visitor<'de>,
+                {
+                    self.record($name, &[]);
+                    self.with_context(|_| visitor.$visit($sample))
+                }
+            }
+        )*
+    };
+}
+
+/// Implement `deserialize_<ty>` by recording an unsupported variable.
+macro_rules! unsupported_types {
+    ($($ty:ident($($arg:ident: $arg_ty:ty),*))*) => {
+        $(
+            paste::paste! {
+                fn [<deserialize_ $ty>]<V>(self, $(_: $arg_ty,)* _visitor: V) -> Result<V::Value>
+                where
+                    V: serde::de::Visitor<'de>,
+                {
+                    self.unsupported(Error::unsupported_type(stringify!($ty)))
+                }
+            }
+        )*
+    };
+}
+
+/// A [`serde::Deserializer`] that records the variables it is asked for, visiting sample values.
+struct Tracer<'a> {
+    options: &'a Options,
+    /// Unknown for the top-level type and enum payloads, which are not variables of their own.
+    location: Option<Location>,
+    /// The location of the variables nested under this one.
+    children: Location,
+    /// Whether this variable is an `Option` or nested under one.
+    optional: bool,
+    /// The names of the structs, enums and newtypes this variable is nested under.
+    ancestors: Vec<&'static str>,
+    /// The paths of the fields that rejected their sample value in a previous attempt.
+    failed: &'a [String],
+    variables: &'a RefCell<Vec<Variable>>,
+}
+
+impl<'a> Tracer<'a> {
+    /// The tracer of the variable at `location`, recorded into `variables`.
+    fn nested(&self, location: Location, variables: &'a RefCell<Vec<Variable>>) -> Self {
+        let children = Location {
+            key: format!(
+                "{}{}",
+                location.key,
+                self.options.separator.as_deref().unwrap_or_default()
+            ),
+            ..location.clone()
+        };
+
+        Self {
+            options: self.options,
+            location: Some(location),
+            children,
+            optional: self.optional,
+            ancestors: self.ancestors.clone(),
+            failed: self.failed,
+            variables,
+        }
+    }
+
+    /// The tracer of the payload of an enum, which is either part of the value or nested under
+    /// the variable.
+    fn payload(&self) -> Self {
+        Self {
+            options: self.options,
+            location: None,
+            children: self.children.clone(),
+            optional: self.optional,
+            ancestors: self.ancestors.clone(),
+            failed: self.failed,
+            variables: self.variables,
+        }
+    }
+
+    /// Record this variable.
+    fn record(&self, ty: impl Into<String>, variants: &[&'static str]) {
+        let Some(location) = &self.location else {
+            return;
+        };
+
+        self.push(Variable {
+            key: location.key.clone(),
+            path: location.path.clone(),
+            ty: ty.into(),
+            required: !self.optional,
+            variants: variants.to_vec(),
+        });
+    }
+
+    /// Record `variable`, unless it is already known.
+    fn push(&self, variable: Variable) {
+        let mut variables = self.variables.borrow_mut();
+
+        if variables.iter().all(|known| known.key != variable.key) {
+            variables.push(variable);
+        }
+    }
+
+    /// Record this variable as `unsupported` and fail with `error`, so that a type that cannot
+    /// be deserialized or traced is not left out.
+    fn unsupported<T>(self, error: Error) -> Result<T> {
+        self.record("unsupported", &[]);
+        self.with_context(|_| Err(error))
+    }
+
+    /// Enter the type named `name`, unless this variable is already nested under it (i.e. the
+    /// type is recursive), which would never end.
+    fn enter(mut self, name: &'static str) -> Result<Self> {
+        if self.ancestors.contains(&name) {
+            return self.unsupported(serde::de::Error::custom(format_args!(
+                "`{name}` is recursive"
+            )));
+        }
+
+        self.ancestors.push(name);
+
+        Ok(self)
+    }
+
+    /// The index of the last failure of the field at `path`, or of one nested under it.
+    fn last_failure(&self, path: &str) -> Option<usize> {
+        self.failed.iter().rposition(|failed| {
+            failed
+                .strip_prefix(path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+
+    /// Call `f`, giving the variable name and field path as context to its error.
+    fn with_context<T>(self, f: impl FnOnce(Self) -> Result<T>) -> Result<T> {
+        let location = self.location.clone();
+
+        f(self).map_err(|error| match location {
+            Some(location) => error.with_context(location.key, location.path, None, false),
+            None => error,
+        })
+    }
+
+    /// Visit the fields of a struct, or a single entry of a map (named `*`).
+    ///
+    /// The fields that failed in a previous attempt are visited last, the most recent failure
+    /// first, so that the others are reached.
+    fn fields<'de, V>(self, fields: Option<&'static [&'static str]>, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        // Nested variables are only read if there is a separator.
+        if !self.children.path.is_empty() && self.options.separator.is_none() {
+            return self.unsupported(Error::unsupported_type(match fields {
+                Some(_) => "struct",
+                None => "map",
+            }));
+        }
+
+        let fields: Vec<Option<&'static str>> = match fields {
+            Some(fields) => {
+                let mut fields = fields.to_vec();
+                fields.sort_by_key(|field| {
+                    self.last_failure(&self.children.nested(field).path)
+                        .map(Reverse)
+                });
+
+                fields.into_iter().map(Some).collect()
+            }
+            None => vec![None],
+        };
+
+        self.with_context(|tracer| {
+            visitor.visit_map(Fields {
+                tracer: &tracer,
+                fields: fields.into_iter(),
+                field: None,
+            })
+        })
+    }
+
+    /// Visit `len` elements, recording a list if they are values rather than nested variables.
+    fn elements<'de, V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let variables = RefCell::new(Vec::new());
+        let mut elements = Elements {
+            tracer: &self,
+            variables: &variables,
+            index: 0,
+            len,
+        };
+
+        let result = visitor.visit_seq(&mut elements);
+        let keys: Vec<String> = (0..elements.index)
+            .map(|index| self.children.nested(&index.to_string()).key)
+            .collect();
+        let variables = variables.into_inner();
+
+        if !variables.is_empty()
+            && variables
+                .iter()
+                .all(|variable| keys.contains(&variable.key))
+        {
+            let types: Vec<&str> = variables.iter().map(|variable| variable.ty()).collect();
+            let variants: Vec<&'static str> = variables
+                .iter()
+                .flat_map(|variable| variable.variants.iter().copied())
+                .collect();
+
+            self.record(format!("list of {}", types.join(", ")), &variants);
+        } else {
+            for variable in variables {
+                self.push(variable);
+            }
+        }
+
+        self.with_context(|_| result)
+    }
+}
+
+impl<'de> serde::de::Deserializer<'de> for Tracer<'_> {
+    type Error = Error;
+
+    // The variables of e.g. flattened or internally tagged types are only known from the values
+    // they are given.
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.unsupported(serde::de::Error::custom(
+            "self-describing types cannot be traced",
+        ))
+    }
+
+    trace! {
+        bool("bool") => visit_bool(false);
+        i8("i8") => visit_i8(0);
+        i16("i16") => visit_i16(0);
+        i32("i32") => visit_i32(0);
+        i64("i64") => visit_i64(0);
+        i128("i128") => visit_i128(0);
+        u8("u8") => visit_u8(0);
+        u16("u16") => visit_u16(0);
+        u32("u32") => visit_u32(0);
+        u64("u64") => visit_u64(0);
+        u128("u128") => visit_u128(0);
+        f32("f32") => visit_f32(0.0);
+        f64("f64") => visit_f64(0.0);
+        char("char") => visit_char('\0');
+        str("string") => visit_str("");
+        string("string") => visit_str("");
+        identifier("string") => visit_str("");
+    }
+
+    fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.optional = true;
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self.enter(name)?)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.elements(1, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.elements(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.fields(None, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.enter(name)?.fields(Some(fields), visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let tracer = self.enter(name)?;
+
+        tracer.record("enum", variants);
+        tracer.with_context(|tracer| {
+            visitor.visit_enum(TracedVariant {
+                variant: variants.first().copied().unwrap_or_default(),
+                payload: tracer.payload(),
+            })
+        })
+    }
+
+    // Required for skipping fields when visiting maps.
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    unsupported_types! {
+        bytes() byte_buf() unit() unit_struct(name: &'static str)
+        tuple_struct(name: &'static str, len: usize)
+    }
+}
+
+/// The fields of a traced struct, or the single entry of a traced map.
+struct Fields<'a, 'b> {
+    tracer: &'b Tracer<'a>,
+    /// The struct fields, or `None` for the entry of a map.
+    fields: std::vec::IntoIter<Option<&'static str>>,
+    field: Option<Option<&'static str>>,
+}
+
+impl<'de> serde::de::MapAccess<'de> for Fields<'_, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        self.field = self.fields.next();
+
+        match self.field {
+            Some(Some(field)) => {
+                let key: StrDeserializer<Error> = field.into_deserializer();
+                seed.deserialize(key).map(Some)
+            }
+            Some(None) => seed.deserialize(Placeholder).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let segment = self.field.flatten().unwrap_or("*");
+        let location = self.tracer.children.nested(segment);
+
+        seed.deserialize(self.tracer.nested(location, self.tracer.variables))
+    }
+}
+
+/// The elements of a traced sequence or tuple, recorded separately.
+struct Elements<'a, 'b> {
+    tracer: &'b Tracer<'a>,
+    variables: &'b RefCell<Vec<Variable>>,
+    index: usize,
+    len: usize,
+}
+
+impl<'de> serde::de::SeqAccess<'de> for Elements<'_, '_> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        if self.index == self.len {
+            return Ok(None);
+        }
+
+        let location = self.tracer.children.nested(&self.index.to_string());
+        self.index += 1;
+
+        seed.deserialize(self.tracer.nested(location, self.variables))
+            .map(Some)
+    }
+}
+
+/// The first variant of a traced enum.
+struct TracedVariant<'a> {
+    variant: &'static str,
+    payload: Tracer<'a>,
+}
+
+impl<'a, 'de> serde::de::EnumAccess<'de> for TracedVariant<'a> {
+    type Error = Error;
+    type Variant = Tracer<'a>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let variant: StrDeserializer<Error> = self.variant.into_deserializer();
+        Ok((seed.deserialize(variant)?, self.payload))
+    }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for Tracer<'_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        serde::de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        serde::de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
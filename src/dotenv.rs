@@ -0,0 +1,417 @@
+use std::{io::Read, path::Path};
+
+use serde::Deserialize;
+
+use crate::{options::DEFAULT_OPTIONS, Error, Result};
+
+/// Deserialize an instance of `T` from the contents of a dotenv file, see [dotenv files].
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     timeout: u16,
+///     host: std::net::IpAddr,
+/// }
+///
+/// let config: Config = de_env::from_dotenv_str(
+///     r#"
+///     export TIMEOUT=12 # Seconds
+///     HOST="127.0.0.1"
+///     "#,
+/// )?;
+///
+/// println!("{config:#?}");
+/// # Ok::<(), de_env::Error>(())
+/// ```
+///
+/// # Errors
+/// This conversion can fail if `input` is not a valid dotenv file, if trying to deserialize
+/// [unsupported types], or if `T`'s implementation of `Deserialize` decides that something is
+/// wrong with the data.
+///
+/// [dotenv files]: crate#dotenv-files
+/// [unsupported types]: crate#unsupported-types
+pub fn from_dotenv_str<'de, T>(input: &str) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    DEFAULT_OPTIONS.from_dotenv_str(input)
+}
+
+/// Deserialize an instance of `T` from the dotenv file at `path`, see [dotenv files].
+///
+/// # Example
+///
+/// Assuming we have a `.env` file in the current directory:
+///
+/// ```rust,no_run
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     timeout: u16,
+///     host: std::net::IpAddr,
+/// }
+///
+/// let config: Config = de_env::from_dotenv_path(".env")?;
+///
+/// println!("{config:#?}");
+/// # Ok::<(), de_env::Error>(())
+/// ```
+///
+/// # Errors
+/// This conversion can fail if the file cannot be read or is not a valid dotenv file, if trying to
+/// deserialize [unsupported types], or if `T`'s implementation of `Deserialize` decides that
+/// something is wrong with the data.
+///
+/// [dotenv files]: crate#dotenv-files
+/// [unsupported types]: crate#unsupported-types
+pub fn from_dotenv_path<'de, T>(path: impl AsRef<Path>) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    DEFAULT_OPTIONS.from_dotenv_path(path)
+}
+
+/// Deserialize an instance of `T` from a reader over the contents of a dotenv file, see
+/// [dotenv files].
+///
+/// # Errors
+/// This conversion can fail if the reader fails or its contents are not a valid dotenv file, if
+/// trying to deserialize [unsupported types], or if `T`'s implementation of `Deserialize` decides
+/// that something is wrong with the data.
+///
+/// [dotenv files]: crate#dotenv-files
+/// [unsupported types]: crate#unsupported-types
+pub fn from_dotenv_reader<'de, T>(reader: impl Read) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    DEFAULT_OPTIONS.from_dotenv_reader(reader)
+}
+
+/// Parse the contents of a dotenv file into key-value pairs, in order.
+///
+/// If `interpolate` is set, the `$` of single-quoted values and of `\$` escapes is escaped as
+/// `$$` so that it is taken literally, see [interpolation].
+///
+/// [interpolation]: crate#interpolation
+pub(crate) fn parse(input: &str, interpolate: bool) -> Result<Vec<(String, String)>> {
+    Parser {
+        input,
+        position: 0,
+        interpolate,
+    }
+    .parse()
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    interpolate: bool,
+}
+
+impl Parser<'_> {
+    fn parse(mut self) -> Result<Vec<(String, String)>> {
+        let mut pairs = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            match self.peek() {
+                None => return Ok(pairs),
+                Some('\n') => {
+                    self.bump();
+                }
+                Some('#') => self.skip_line(),
+                Some(_) => pairs.push(self.pair()?),
+            }
+        }
+    }
+
+    /// Parse a `KEY=value` line, optionally prefixed with `export`.
+    fn pair(&mut self) -> Result<(String, String)> {
+        let mut key = self.key()?;
+
+        if key == "export" && self.peek().is_some_and(is_whitespace) {
+            self.skip_whitespace();
+            key = self.key()?;
+        }
+
+        self.skip_whitespace();
+
+        if self.peek() != Some('=') {
+            return Err(self.error(self.position, "expected `=` after the key"));
+        }
+
+        self.bump();
+
+        let equals = self.position;
+        self.skip_whitespace();
+
+        let value = match self.peek() {
+            Some('\'') => self.single_quoted()?,
+            Some('"') => self.double_quoted()?,
+            // A comment following whitespace, e.g. `KEY= # Comment`, leaves the value empty.
+            Some('#') if self.position > equals => String::new(),
+            _ => self.unquoted(),
+        };
+
+        self.skip_whitespace();
+
+        match self.peek() {
+            None | Some('\n') => {}
+            Some('#') => self.skip_line(),
+            Some(_) => return Err(self.error(self.position, "expected the end of the line")),
+        }
+
+        Ok((key, value))
+    }
+
+    fn key(&mut self) -> Result<String> {
+        let start = self.position;
+
+        while self.peek().is_some_and(is_key) {
+            self.bump();
+        }
+
+        if start == self.position {
+            return Err(self.error(start, "expected a key"));
+        }
+
+        Ok(self.input[start..self.position].to_owned())
+    }
+
+    /// Parse a value up to the end of the line or a comment (i.e. a `#` following whitespace),
+    /// trimmed of trailing whitespace.
+    fn unquoted(&mut self) -> String {
+        let start = self.position;
+
+        while let Some(char) = self.peek() {
+            if char == '\n'
+                || (char == '#' && self.input[start..self.position].ends_with(is_whitespace))
+            {
+                break;
+            }
+
+            self.bump();
+        }
+
+        self.input[start..self.position]
+            .trim_end_matches(is_whitespace)
+            .to_owned()
+    }
+
+    /// Parse a value enclosed in single quotes, taken literally.
+    fn single_quoted(&mut self) -> Result<String> {
+        let start = self.position;
+        self.bump();
+
+        let contents = self.position;
+
+        loop {
+            match self.bump() {
+                Some('\'') => {
+                    let value = &self.input[contents..self.position - 1];
+
+                    return Ok(if self.interpolate {
+                        value.replace('$', "$$")
+                    } else {
+                        value.to_owned()
+                    });
+                }
+                Some(_) => {}
+                None => return Err(self.error(start, "unterminated quote")),
+            }
+        }
+    }
+
+    /// Parse a value enclosed in double quotes, where `\n`, `\r`, `\t`, `\\`, `\"` and `\$` are
+    /// escape sequences (any other backslash is kept as-is).
+    fn double_quoted(&mut self) -> Result<String> {
+        let start = self.position;
+        self.bump();
+
+        let mut value = String::new();
+
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(value),
+                Some('\\') => {
+                    let escaped = match self.peek() {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some(char @ ('\\' | '"')) => char,
+                        Some('$') => {
+                            if self.interpolate {
+                                value.push('$');
+                            }
+
+                            '$'
+                        }
+                        _ => {
+                            value.push('\\');
+                            continue;
+                        }
+                    };
+
+                    self.bump();
+                    value.push(escaped);
+                }
+                Some(char) => value.push(char),
+                None => return Err(self.error(start, "unterminated quote")),
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.position += char.len_utf8();
+        Some(char)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while self.peek().is_some_and(|char| char != '\n') {
+            self.bump();
+        }
+    }
+
+    /// An error at the given byte position, reported as a line and column (both starting at 1).
+    fn error(&self, position: usize, reason: &'static str) -> Error {
+        let before = &self.input[..position];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        Error::invalid_dotenv(
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+            reason,
+        )
+    }
+}
+
+/// Whitespace within a line, a carriage return is included so that CRLF line endings are
+/// supported.
+fn is_whitespace(char: char) -> bool {
+    matches!(char, ' ' | '\t' | '\r')
+}
+
+/// The characters of a key.
+pub(crate) fn is_key(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_' || char == '.'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn pairs() {
+        let pairs = parse(
+            "# Comment\n\
+            A=1\n\
+            \n\
+            export B = two words  # Comment\n\
+            C=#not a comment\n\
+            D=\r\n\
+            \tE.F=x\n\
+            G= # Comment\n\
+            export=3",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pairs,
+            [
+                ("A", "1"),
+                ("B", "two words"),
+                ("C", "#not a comment"),
+                ("D", ""),
+                ("E.F", "x"),
+                ("G", ""),
+                ("export", "3"),
+            ]
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        );
+    }
+
+    #[test]
+    fn quoted() {
+        let pairs = parse(
+            r#"A='single \n "quoted"' # Comment
+B="double\n\t\"quoted\" \$HOME C:\Users"
+C="multi
+line"
+D=''
+"#,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pairs,
+            [
+                ("A", r#"single \n "quoted""#),
+                ("B", "double\n\t\"quoted\" $HOME C:\\Users"),
+                ("C", "multi\nline"),
+                ("D", ""),
+            ]
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        );
+
+        // Literal `$` are escaped when interpolating.
+        let pairs = parse(
+            r#"A='${B} $$'
+B="\${B} ${B}"
+C=${B}
+"#,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pairs,
+            [("A", "$${B} $$$$"), ("B", "$${B} ${B}"), ("C", "${B}")]
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let error = |input| parse(input, false).unwrap_err().to_string();
+
+        assert_eq!(
+            error("A=1\nB"),
+            "invalid dotenv file at line 2, column 2: expected `=` after the key"
+        );
+        assert_eq!(
+            error("A=1\n  =2"),
+            "invalid dotenv file at line 2, column 3: expected a key"
+        );
+        assert_eq!(
+            error("A=\"1\nB=2"),
+            "invalid dotenv file at line 1, column 3: unterminated quote"
+        );
+        assert_eq!(
+            error("A='1'2"),
+            "invalid dotenv file at line 1, column 6: expected the end of the line"
+        );
+        assert_eq!(
+            error("é=1"),
+            "invalid dotenv file at line 1, column 1: expected a key"
+        );
+    }
+}
@@ -2,71 +2,548 @@ use std::{
     ffi::{OsStr, OsString},
     fmt::{self, Display},
     num::{ParseFloatError, ParseIntError},
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 
+use crate::Format;
+
 /// Convenience alias for a `Result` with this crate [`Error`] type.
 pub type Result<T> = std::result::Result<T, Error>;
 
-/// Represent an error that may arise when deserializing.
+/// Represent an error that may arise when deserializing or serializing.
+///
+/// Errors raised while deserializing a variable are given its name ([`Error::key`]), field path
+/// ([`Error::path`]) and value ([`Error::value`]) as context.
+#[derive(Debug, Clone)]
+pub struct Error(Box<ErrorImpl>);
+
 #[derive(Debug, Clone)]
-pub struct Error(Box<ErrorCode>);
+struct ErrorImpl {
+    code: ErrorCode,
+    key: Option<Box<str>>,
+    path: Option<Box<str>>,
+    value: Option<Box<OsStr>>,
+    redacted: bool,
+}
+
+/// Replaces redacted values and the messages that may contain them.
+const REDACTED: &str = "[REDACTED]";
+
+/// The kind of an [`Error`], see [`Error::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A custom error raised by a `Deserialize` or `Serialize` implementation (e.g. an invalid
+    /// value or type).
+    Message,
+    /// A type that cannot be deserialized or serialized, see [unsupported types].
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    UnsupportedType,
+    /// A value that is not valid unicode.
+    InvalidUnicode,
+    /// A value that is not a valid integer.
+    InvalidInteger,
+    /// A value that is not a valid float.
+    InvalidFloat,
+    /// A value that is not a valid boolean, see [boolean parsing].
+    ///
+    /// [boolean parsing]: crate#boolean-parsing
+    InvalidBool,
+    /// A field with nested variables but no variable of its own.
+    MissingValue,
+    /// A struct or map field with a variable of its own, whereas it is read from nested
+    /// variables.
+    UnexpectedValue,
+    /// A value that is not a valid sequence, see [sequences].
+    ///
+    /// [sequences]: crate#sequences
+    InvalidSequence,
+    /// An element of a sequence of structs with an invalid index.
+    InvalidIndex,
+    /// A sequence of structs with a gap in its indices.
+    MissingIndex,
+    /// A required field without a variable.
+    MissingField,
+    /// A variable that does not match any field, for structs that deny unknown fields.
+    UnknownField,
+    /// A file that could not be read, see [`Options::file_suffix`].
+    ///
+    /// [`Options::file_suffix`]: crate::Options::file_suffix
+    ReadFile,
+    /// A subdirectory, see [`Options::reject_subdirectories`].
+    ///
+    /// [`Options::reject_subdirectories`]: crate::Options::reject_subdirectories
+    UnexpectedDirectory,
+    /// A reader that failed, see [`from_dotenv_reader`].
+    ///
+    /// [`from_dotenv_reader`]: crate::from_dotenv_reader
+    Io,
+    /// A dotenv file that could not be parsed, see [dotenv files].
+    ///
+    /// [dotenv files]: crate#dotenv-files
+    InvalidDotenv,
+    /// A value that is not a valid template, see [interpolation].
+    ///
+    /// [interpolation]: crate#interpolation
+    InvalidInterpolation,
+    /// A variable referencing itself, directly or not, see [interpolation].
+    ///
+    /// [interpolation]: crate#interpolation
+    ReferenceCycle,
+    /// A value that is too long once interpolated, e.g. because of references repeating large
+    /// values, see [interpolation].
+    ///
+    /// [interpolation]: crate#interpolation
+    InterpolationTooLong,
+    /// A required reference (i.e. `${VAR:?message}`) to a variable that is not set, see
+    /// [interpolation].
+    ///
+    /// [interpolation]: crate#interpolation
+    MissingReference,
+    /// A variable that cannot be written in a [`Format`](crate::Format), e.g. a multiline value
+    /// in a Docker env file.
+    UnsupportedValue,
+    /// Several errors, see [`Error::errors`].
+    Collected,
+}
 
 #[derive(Debug, Clone)]
 enum ErrorCode {
     Message(Box<str>),
     UnsupportedType(&'static str),
-    InvalidUnicode(Box<OsStr>),
+    Unserializable(&'static str),
+    MissingSeparator,
+    InvalidUnicode,
     InvalidInteger(ParseIntError),
     InvalidFloat(ParseFloatError),
-    InvalidBool(Box<OsStr>),
+    InvalidBool,
+    MissingValue,
+    UnexpectedValue,
+    InvalidSequence(usize, &'static str),
+    InvalidIndex(Box<str>),
+    MissingIndex(usize),
+    MissingField(&'static str),
+    UnknownField(Box<str>, &'static [&'static str]),
+    ReadFile(Box<Path>, Arc<std::io::Error>),
+    UnexpectedDirectory(Box<Path>),
+    Io(Arc<std::io::Error>),
+    InvalidDotenv(usize, usize, &'static str),
+    InvalidInterpolation(&'static str),
+    ReferenceCycle(Box<[Box<str>]>),
+    InterpolationTooLong(usize),
+    MissingReference(Box<[Box<str>]>, Box<str>),
+    UnsupportedValue(Format, &'static str),
+    Collected(Box<[Error]>),
 }
 
 impl Error {
     fn new(code: ErrorCode) -> Self {
-        Self(Box::new(code))
+        Self(Box::new(ErrorImpl {
+            code,
+            key: None,
+            path: None,
+            value: None,
+            redacted: false,
+        }))
+    }
+
+    /// The kind of this error.
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        match self.0.code {
+            ErrorCode::Message(_) => ErrorKind::Message,
+            ErrorCode::UnsupportedType(_)
+            | ErrorCode::Unserializable(_)
+            | ErrorCode::MissingSeparator => ErrorKind::UnsupportedType,
+            ErrorCode::InvalidUnicode => ErrorKind::InvalidUnicode,
+            ErrorCode::InvalidInteger(_) => ErrorKind::InvalidInteger,
+            ErrorCode::InvalidFloat(_) => ErrorKind::InvalidFloat,
+            ErrorCode::InvalidBool => ErrorKind::InvalidBool,
+            ErrorCode::MissingValue => ErrorKind::MissingValue,
+            ErrorCode::UnexpectedValue => ErrorKind::UnexpectedValue,
+            ErrorCode::InvalidSequence(..) => ErrorKind::InvalidSequence,
+            ErrorCode::InvalidIndex(_) => ErrorKind::InvalidIndex,
+            ErrorCode::MissingIndex(_) => ErrorKind::MissingIndex,
+            ErrorCode::MissingField(_) => ErrorKind::MissingField,
+            ErrorCode::UnknownField(..) => ErrorKind::UnknownField,
+            ErrorCode::ReadFile(..) => ErrorKind::ReadFile,
+            ErrorCode::UnexpectedDirectory(_) => ErrorKind::UnexpectedDirectory,
+            ErrorCode::Io(_) => ErrorKind::Io,
+            ErrorCode::InvalidDotenv(..) => ErrorKind::InvalidDotenv,
+            ErrorCode::InvalidInterpolation(_) => ErrorKind::InvalidInterpolation,
+            ErrorCode::ReferenceCycle(_) => ErrorKind::ReferenceCycle,
+            ErrorCode::InterpolationTooLong(_) => ErrorKind::InterpolationTooLong,
+            ErrorCode::MissingReference(..) => ErrorKind::MissingReference,
+            ErrorCode::UnsupportedValue(..) => ErrorKind::UnsupportedValue,
+            ErrorCode::Collected(_) => ErrorKind::Collected,
+        }
+    }
+
+    /// The name of the variable that caused this error, if known.
+    #[must_use]
+    pub fn key(&self) -> Option<&str> {
+        self.0.key.as_deref()
+    }
+
+    /// The path of the field that caused this error, if known.
+    ///
+    /// Segments are separated by dots, e.g. `DATABASE.HOST` for the `HOST` field of a struct
+    /// nested under the `DATABASE` field, or `SERVERS.0` for the first element of a sequence.
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        self.0.path.as_deref()
+    }
+
+    /// The value of the variable that caused this error, if known and not [redacted].
+    ///
+    /// [redacted]: crate::Options::redaction
+    #[must_use]
+    pub fn value(&self) -> Option<&OsStr> {
+        self.0.value.as_deref()
+    }
+
+    /// The individual errors, i.e. every error collected if [`Options::collect_errors`] is
+    /// enabled and more than one error occurred, otherwise only this error.
+    ///
+    /// [`Options::collect_errors`]: crate::Options::collect_errors
+    #[must_use]
+    pub fn errors(&self) -> &[Error] {
+        match &self.0.code {
+            ErrorCode::Collected(errors) => errors,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    /// Give the variable name, field path and value as context, unless the error already has
+    /// context (i.e. the innermost variable is kept).
+    ///
+    /// If `redact` is set, the error is [redacted](Self::redact) instead of given the value.
+    pub(crate) fn with_context(
+        mut self,
+        key: String,
+        path: String,
+        value: Option<OsString>,
+        redact: bool,
+    ) -> Self {
+        if self.0.key.is_some() {
+            return self;
+        }
+
+        self.0.key = Some(key.into_boxed_str());
+        self.0.path = Some(path.into_boxed_str());
+
+        if redact {
+            self = self.redact();
+        } else if let Some(value) = value {
+            self.0.value = Some(value.into_boxed_os_str());
+        }
+
+        self
+    }
+
+    /// Give the variable name as context, unless the error already has context.
+    pub(crate) fn with_key(mut self, key: String) -> Self {
+        if self.0.key.is_none() {
+            self.0.key = Some(key.into_boxed_str());
+        }
+
+        self
+    }
+
+    /// Attach `value`, unless a value is already attached.
+    pub(crate) fn with_value(mut self, value: OsString) -> Self {
+        if self.0.value.is_none() {
+            self.0.value = Some(value.into_boxed_os_str());
+        }
+
+        self
+    }
+
+    /// Detach the value of this error, and mask every message that may contain it (i.e. those
+    /// of `Deserialize` implementations and required references).
+    pub(crate) fn redact(mut self) -> Self {
+        self.0.value = None;
+        self.0.redacted = true;
+
+        match &mut self.0.code {
+            ErrorCode::Message(message) => {
+                *message = format!("invalid value `{REDACTED}`").into_boxed_str();
+            }
+            ErrorCode::MissingReference(_, message) if !message.is_empty() => {
+                *message = REDACTED.into();
+            }
+            _ => {}
+        }
+
+        self
     }
 
     pub(crate) fn unsupported_type(ty: &'static str) -> Self {
         Self::new(ErrorCode::UnsupportedType(ty))
     }
 
+    pub(crate) fn unserializable(ty: &'static str) -> Self {
+        Self::new(ErrorCode::Unserializable(ty))
+    }
+
+    pub(crate) fn missing_separator() -> Self {
+        Self::new(ErrorCode::MissingSeparator)
+    }
+
     pub(crate) fn invalid_unicode(value: OsString) -> Self {
-        Self::new(ErrorCode::InvalidUnicode(value.into_boxed_os_str()))
+        Self::new(ErrorCode::InvalidUnicode).with_value(value)
     }
 
     pub(crate) fn invalid_bool(value: OsString) -> Self {
-        Self::new(ErrorCode::InvalidBool(value.into_boxed_os_str()))
+        Self::new(ErrorCode::InvalidBool).with_value(value)
+    }
+
+    pub(crate) fn missing_value() -> Self {
+        Self::new(ErrorCode::MissingValue)
+    }
+
+    pub(crate) fn unexpected_value() -> Self {
+        Self::new(ErrorCode::UnexpectedValue)
+    }
+
+    pub(crate) fn invalid_sequence(position: usize, reason: &'static str) -> Self {
+        Self::new(ErrorCode::InvalidSequence(position, reason))
+    }
+
+    pub(crate) fn invalid_index(index: &str) -> Self {
+        Self::new(ErrorCode::InvalidIndex(index.into()))
+    }
+
+    pub(crate) fn missing_index(index: usize) -> Self {
+        Self::new(ErrorCode::MissingIndex(index))
+    }
+
+    pub(crate) fn read_file(path: PathBuf, error: std::io::Error) -> Self {
+        Self::new(ErrorCode::ReadFile(path.into_boxed_path(), Arc::new(error)))
+    }
+
+    pub(crate) fn unexpected_directory(path: PathBuf) -> Self {
+        Self::new(ErrorCode::UnexpectedDirectory(path.into_boxed_path()))
+    }
+
+    pub(crate) fn io(error: std::io::Error) -> Self {
+        Self::new(ErrorCode::Io(Arc::new(error)))
+    }
+
+    pub(crate) fn invalid_dotenv(line: usize, column: usize, reason: &'static str) -> Self {
+        Self::new(ErrorCode::InvalidDotenv(line, column, reason))
+    }
+
+    pub(crate) fn invalid_interpolation(reason: &'static str) -> Self {
+        Self::new(ErrorCode::InvalidInterpolation(reason))
+    }
+
+    pub(crate) fn reference_cycle(chain: &[&str]) -> Self {
+        Self::new(ErrorCode::ReferenceCycle(
+            chain.iter().copied().map(Box::from).collect(),
+        ))
+    }
+
+    pub(crate) fn interpolation_too_long(limit: usize) -> Self {
+        Self::new(ErrorCode::InterpolationTooLong(limit))
+    }
+
+    pub(crate) fn missing_reference(chain: &[&str], message: &str) -> Self {
+        Self::new(ErrorCode::MissingReference(
+            chain.iter().copied().map(Box::from).collect(),
+            message.into(),
+        ))
+    }
+
+    pub(crate) fn unsupported_value(format: Format, reason: &'static str) -> Self {
+        Self::new(ErrorCode::UnsupportedValue(format, reason))
+    }
+
+    /// Combine the errors collected, unless there is only one.
+    pub(crate) fn collected(mut errors: Vec<Error>) -> Self {
+        if errors.len() == 1 {
+            return errors.remove(0);
+        }
+
+        Self::new(ErrorCode::Collected(errors.into_boxed_slice()))
+    }
+
+    /// The field reported missing or unknown by this error, if any.
+    pub(crate) fn field(&self) -> Option<&str> {
+        match &self.0.code {
+            ErrorCode::MissingField(field) => Some(field),
+            ErrorCode::UnknownField(field, _) => Some(field),
+            _ => None,
+        }
     }
 }
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        match self.0.as_ref() {
+        if let Some(key) = self.key() {
+            formatter.write_fmt(format_args!("`{key}`: "))?;
+        }
+
+        let value = || match self.value() {
+            _ if self.0.redacted => REDACTED.into(),
+            value => value.unwrap_or_default().to_string_lossy(),
+        };
+
+        match &self.0.code {
             ErrorCode::Message(msg) => formatter.write_str(msg),
             ErrorCode::UnsupportedType(ty) => formatter.write_fmt(format_args!(
                 "`{ty}` cannot be deserialized from environment variables"
             )),
-            ErrorCode::InvalidUnicode(value) => formatter.write_fmt(format_args!(
+            ErrorCode::Unserializable(ty) => formatter.write_fmt(format_args!(
+                "`{ty}` cannot be serialized to environment variables"
+            )),
+            ErrorCode::MissingSeparator => {
+                formatter.write_str("nested variables cannot be serialized without a separator")
+            }
+            ErrorCode::InvalidUnicode => formatter.write_fmt(format_args!(
                 "`{}` could not be deserialized and parsed as it is not valid unicode",
-                value.to_string_lossy()
+                value()
             )),
             ErrorCode::InvalidInteger(err) => err.fmt(formatter),
             ErrorCode::InvalidFloat(err) => err.fmt(formatter),
-            ErrorCode::InvalidBool(value) => formatter.write_fmt(format_args!(
-                "`{}` is not a boolean",
-                value.to_string_lossy()
+            ErrorCode::InvalidBool => {
+                formatter.write_fmt(format_args!("`{}` is not a boolean", value()))
+            }
+            ErrorCode::MissingValue => {
+                formatter.write_str("expected a value but only found nested variables")
+            }
+            ErrorCode::UnexpectedValue => {
+                formatter.write_str("expected only nested variables but found a value")
+            }
+            ErrorCode::InvalidSequence(position, reason) => formatter.write_fmt(format_args!(
+                "invalid sequence at position {position}: {reason}"
+            )),
+            ErrorCode::InvalidIndex(index) => {
+                formatter.write_fmt(format_args!("`{index}` is not a valid sequence index"))
+            }
+            ErrorCode::MissingIndex(index) => {
+                formatter.write_fmt(format_args!("missing sequence element at index {index}"))
+            }
+            ErrorCode::MissingField(field) => formatter.write_fmt(format_args!(
+                "missing field `{}`",
+                self.path().unwrap_or(field)
             )),
+            ErrorCode::UnknownField(field, expected) => {
+                formatter.write_fmt(format_args!(
+                    "unknown field `{}`",
+                    self.path().unwrap_or(field)
+                ))?;
+
+                match expected {
+                    [] => formatter.write_str(", there are no fields"),
+                    [expected] => formatter.write_fmt(format_args!(", expected `{expected}`")),
+                    [first, second] => {
+                        formatter.write_fmt(format_args!(", expected `{first}` or `{second}`"))
+                    }
+                    [first, rest @ ..] => {
+                        formatter.write_fmt(format_args!(", expected one of `{first}`"))?;
+
+                        for expected in rest {
+                            formatter.write_fmt(format_args!(", `{expected}`"))?;
+                        }
+
+                        Ok(())
+                    }
+                }
+            }
+            ErrorCode::ReadFile(path, error) => {
+                formatter.write_fmt(format_args!("could not read `{}`: {error}", path.display()))
+            }
+            ErrorCode::UnexpectedDirectory(path) => {
+                formatter.write_fmt(format_args!("`{}` is a directory", path.display()))
+            }
+            ErrorCode::Io(error) => error.fmt(formatter),
+            ErrorCode::InvalidDotenv(line, column, reason) => formatter.write_fmt(format_args!(
+                "invalid dotenv file at line {line}, column {column}: {reason}"
+            )),
+            ErrorCode::InvalidInterpolation(reason) => {
+                formatter.write_fmt(format_args!("invalid interpolation: {reason}"))
+            }
+            ErrorCode::ReferenceCycle(chain) => {
+                formatter.write_str("reference cycle: ")?;
+                write_chain(formatter, chain)
+            }
+            ErrorCode::InterpolationTooLong(limit) => {
+                formatter.write_fmt(format_args!("interpolated value longer than {limit} bytes"))
+            }
+            ErrorCode::MissingReference(chain, message) => {
+                formatter.write_fmt(format_args!(
+                    "missing variable `{}`, referenced through ",
+                    chain.last().map_or("", AsRef::as_ref)
+                ))?;
+                write_chain(formatter, chain)?;
+
+                if !message.is_empty() {
+                    formatter.write_fmt(format_args!(": {message}"))?;
+                }
+
+                Ok(())
+            }
+            ErrorCode::UnsupportedValue(format, reason) => {
+                formatter.write_fmt(format_args!("cannot be written to a {format}: {reason}"))
+            }
+            ErrorCode::Collected(errors) => {
+                formatter.write_fmt(format_args!("{} errors occurred:", errors.len()))?;
+
+                for error in errors.iter() {
+                    formatter.write_fmt(format_args!("\n- {error}"))?;
+                }
+
+                Ok(())
+            }
         }
     }
 }
 
-impl std::error::Error for Error {}
+/// Write a chain of variables, e.g. `` `A` -> `B` ``.
+fn write_chain(formatter: &mut fmt::Formatter, chain: &[Box<str>]) -> fmt::Result {
+    for (index, name) in chain.iter().enumerate() {
+        if index > 0 {
+            formatter.write_str(" -> ")?;
+        }
+
+        formatter.write_fmt(format_args!("`{name}`"))?;
+    }
+
+    Ok(())
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.0.code {
+            ErrorCode::ReadFile(_, error) | ErrorCode::Io(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 #[doc(hidden)]
 impl serde::de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
         Self::new(ErrorCode::Message(msg.to_string().into_boxed_str()))
     }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::new(ErrorCode::MissingField(field))
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Self::new(ErrorCode::UnknownField(field.into(), expected))
+    }
+}
+
+#[doc(hidden)]
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::new(ErrorCode::Message(msg.to_string().into_boxed_str()))
+    }
 }
 
 #[doc(hidden)]
@@ -0,0 +1,379 @@
+use std::{fmt, io::Write};
+
+use serde::Serialize;
+
+use crate::{dotenv, options::DEFAULT_OPTIONS, Error, Result};
+
+/// A file format that serialized variables can be written in, see [`to_string`].
+///
+/// Each variable is written on its own line, quoted according to the rules of the format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+    /// A dotenv file (`KEY="value"`), as read by [`from_dotenv_str`](crate::from_dotenv_str).
+    ///
+    /// Values are enclosed in double quotes, where `\n`, `\r`, `\t`, `\\`, `\"` and `\$` are
+    /// escaped.
+    Dotenv,
+    /// A POSIX shell script (`export KEY='value'`), to be sourced.
+    ///
+    /// Values are enclosed in single quotes, a single quote is written as `'\''`. Names must be
+    /// valid shell identifiers (letters, digits and underscores, not starting with a digit).
+    Shell,
+    /// A systemd `EnvironmentFile` (`KEY="value"`).
+    ///
+    /// Values are enclosed in double quotes, where `\\`, `\"`, `` \` `` and `\$` are escaped and
+    /// newlines are kept as-is.
+    Systemd,
+    /// A Docker `--env-file` (`KEY=value`).
+    ///
+    /// Values are written as-is since the format does not support quoting, so they cannot span
+    /// multiple lines.
+    Docker,
+}
+
+/// Serialize `value` into a file in the given `format`, see [serialization].
+///
+/// # Example
+/// ```rust
+/// #[derive(serde::Serialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     timeout: u16,
+///     motd: String,
+/// }
+///
+/// let config = Config {
+///     timeout: 12,
+///     motd: "It's \"fine\"".to_owned(),
+/// };
+///
+/// assert_eq!(
+///     de_env::to_string(&config, de_env::Format::Dotenv)?,
+///     "TIMEOUT=\"12\"\nMOTD=\"It's \\\"fine\\\"\"\n"
+/// );
+/// assert_eq!(
+///     de_env::to_string(&config, de_env::Format::Shell)?,
+///     "export TIMEOUT='12'\nexport MOTD='It'\\''s \"fine\"'\n"
+/// );
+/// # Ok::<(), de_env::Error>(())
+/// ```
+///
+/// # Errors
+/// This conversion can fail if trying to serialize [unsupported types], if `T`'s implementation
+/// of `Serialize` decides to fail, or if a variable cannot be written in `format` (e.g. a
+/// multiline value in a Docker env file).
+///
+/// [serialization]: crate#serialization
+/// [unsupported types]: crate#unsupported-types
+pub fn to_string<T>(value: &T, format: Format) -> Result<String>
+where
+    T: Serialize + ?Sized,
+{
+    DEFAULT_OPTIONS.to_string(value, format)
+}
+
+/// Serialize `value` into a file in the given `format`, written to `writer`, see [`to_string`].
+///
+/// # Errors
+/// This conversion can fail if trying to serialize [unsupported types], if `T`'s implementation
+/// of `Serialize` decides to fail, if a variable cannot be written in `format` or if the writer
+/// fails.
+///
+/// [unsupported types]: crate#unsupported-types
+pub fn to_writer<T>(value: &T, format: Format, writer: impl Write) -> Result<()>
+where
+    T: Serialize + ?Sized,
+{
+    DEFAULT_OPTIONS.to_writer(value, format, writer)
+}
+
+impl Format {
+    /// Write the variables, one per line.
+    pub(crate) fn write(self, vars: Vec<(String, String)>) -> Result<String> {
+        let mut output = String::new();
+
+        for (key, value) in vars {
+            self.write_var(&key, &value, &mut output)
+                .map_err(|error| error.with_key(key))?;
+        }
+
+        Ok(output)
+    }
+
+    fn write_var(self, key: &str, value: &str, output: &mut String) -> Result<()> {
+        if value.contains('\0') {
+            return Err(Error::unsupported_value(
+                self,
+                "values cannot contain nul bytes",
+            ));
+        }
+
+        let valid = match self {
+            Self::Dotenv => !key.is_empty() && key.chars().all(dotenv::is_key),
+            Self::Shell => {
+                !key.starts_with(|char: char| char.is_ascii_digit())
+                    && !key.is_empty()
+                    && key
+                        .chars()
+                        .all(|char| char.is_ascii_alphanumeric() || char == '_')
+            }
+            Self::Systemd | Self::Docker => {
+                !key.is_empty()
+                    && !key.starts_with('#')
+                    && !key
+                        .contains(|char: char| char == '=' || char == '\0' || char.is_whitespace())
+            }
+        };
+
+        if !valid {
+            return Err(Error::unsupported_value(self, "invalid variable name"));
+        }
+
+        match self {
+            Self::Dotenv => {
+                output.push_str(key);
+                output.push_str("=\"");
+
+                for char in value.chars() {
+                    match char {
+                        '\n' => output.push_str(r"\n"),
+                        '\r' => output.push_str(r"\r"),
+                        '\t' => output.push_str(r"\t"),
+                        '\\' | '"' | '$' => {
+                            output.push('\\');
+                            output.push(char);
+                        }
+                        _ => output.push(char),
+                    }
+                }
+
+                output.push('"');
+            }
+            Self::Shell => {
+                output.push_str("export ");
+                output.push_str(key);
+                output.push_str("='");
+                output.push_str(&value.replace('\'', r"'\''"));
+                output.push('\'');
+            }
+            Self::Systemd => {
+                output.push_str(key);
+                output.push_str("=\"");
+
+                for char in value.chars() {
+                    if matches!(char, '\\' | '"' | '`' | '$') {
+                        output.push('\\');
+                    }
+
+                    output.push(char);
+                }
+
+                output.push('"');
+            }
+            Self::Docker => {
+                // Lines are trimmed of a trailing carriage return when read.
+                if value.contains(['\n', '\r']) {
+                    return Err(Error::unsupported_value(
+                        self,
+                        "values cannot contain line breaks",
+                    ));
+                }
+
+                output.push_str(key);
+                output.push('=');
+                output.push_str(value);
+            }
+        }
+
+        output.push('\n');
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Dotenv => "dotenv file",
+            Self::Shell => "shell script",
+            Self::Systemd => "systemd environment file",
+            Self::Docker => "Docker env file",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::Format;
+
+    fn vars() -> Vec<(String, String)> {
+        [
+            ("EMPTY", ""),
+            ("PLAIN", "value"),
+            ("SPACES", "  a  b  "),
+            ("COMMENT", "a # b"),
+            ("QUOTES", r#"It's "quoted" `cmd`"#),
+            ("ESCAPES", r"C:\Users\$HOME \n"),
+            ("VARIABLE", "${HOME} $HOME"),
+            ("UNICODE", "héllo ✓"),
+            ("TAB", "a\tb"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect()
+    }
+
+    fn multiline() -> Vec<(String, String)> {
+        vec![("MULTILINE".to_owned(), "a\nb\r\n'c'\n".to_owned())]
+    }
+
+    #[test]
+    fn dotenv() {
+        for vars in [vars(), multiline()] {
+            let output = Format::Dotenv.write(vars.clone()).unwrap();
+
+            assert_eq!(crate::dotenv::parse(&output, false).unwrap(), vars);
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shell() {
+        for vars in [vars(), multiline()] {
+            let mut script = Format::Shell.write(vars.clone()).unwrap();
+
+            for (key, _) in &vars {
+                script.push_str(&format!("printf '%s\\0' \"${key}\"\n"));
+            }
+
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(script)
+                .env_clear()
+                .output()
+                .unwrap();
+
+            assert!(output.status.success());
+
+            let values: Vec<String> = String::from_utf8(output.stdout)
+                .unwrap()
+                .split_terminator('\0')
+                .map(str::to_owned)
+                .collect();
+
+            assert_eq!(
+                values,
+                vars.into_iter().map(|(_, value)| value).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn systemd() {
+        // In double quotes, systemd.exec(5) unescapes `\\`, `\"`, `` \` `` and `\$`, keeps other
+        // backslashes and newlines, and does not strip whitespace nor comments.
+        assert_eq!(
+            Format::Systemd.write(vars()).unwrap(),
+            concat!(
+                "EMPTY=\"\"\n",
+                "PLAIN=\"value\"\n",
+                "SPACES=\"  a  b  \"\n",
+                "COMMENT=\"a # b\"\n",
+                "QUOTES=\"It's \\\"quoted\\\" \\`cmd\\`\"\n",
+                "ESCAPES=\"C:\\\\Users\\\\\\$HOME \\\\n\"\n",
+                "VARIABLE=\"\\${HOME} \\$HOME\"\n",
+                "UNICODE=\"héllo ✓\"\n",
+                "TAB=\"a\tb\"\n",
+            )
+        );
+        assert_eq!(
+            Format::Systemd.write(multiline()).unwrap(),
+            "MULTILINE=\"a\nb\r\n'c'\n\"\n"
+        );
+        assert_eq!(
+            Format::Systemd
+                .write(vec![("HASH".to_owned(), "#a;b".to_owned())])
+                .unwrap(),
+            "HASH=\"#a;b\"\n"
+        );
+    }
+
+    #[test]
+    fn docker() {
+        // A Docker `--env-file` line is split on the first `=`, and the value is taken as-is:
+        // quotes, backslashes, `$` and `#` are not special past the start of the line.
+        assert_eq!(
+            Format::Docker.write(vars()).unwrap(),
+            concat!(
+                "EMPTY=\n",
+                "PLAIN=value\n",
+                "SPACES=  a  b  \n",
+                "COMMENT=a # b\n",
+                "QUOTES=It's \"quoted\" `cmd`\n",
+                "ESCAPES=C:\\Users\\$HOME \\n\n",
+                "VARIABLE=${HOME} $HOME\n",
+                "UNICODE=héllo ✓\n",
+                "TAB=a\tb\n",
+            )
+        );
+        assert_eq!(
+            Format::Docker
+                .write(vec![("HASH".to_owned(), "#a=b".to_owned())])
+                .unwrap(),
+            "HASH=#a=b\n"
+        );
+
+        let error = Format::Docker.write(multiline()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "`MULTILINE`: cannot be written to a Docker env file: values cannot contain line breaks"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let error = |format: Format, key: &str, value: &str| {
+            format
+                .write(vec![(key.to_owned(), value.to_owned())])
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error(Format::Shell, "1A", ""),
+            "`1A`: cannot be written to a shell script: invalid variable name"
+        );
+        assert_eq!(
+            error(Format::Shell, "A.B", ""),
+            "`A.B`: cannot be written to a shell script: invalid variable name"
+        );
+        assert_eq!(
+            error(Format::Dotenv, "A-B", ""),
+            "`A-B`: cannot be written to a dotenv file: invalid variable name"
+        );
+        assert_eq!(
+            error(Format::Systemd, "A B", ""),
+            "`A B`: cannot be written to a systemd environment file: invalid variable name"
+        );
+        assert_eq!(
+            error(Format::Docker, "#A", ""),
+            "`#A`: cannot be written to a Docker env file: invalid variable name"
+        );
+        assert_eq!(
+            error(Format::Systemd, "A", "\0"),
+            "`A`: cannot be written to a systemd environment file: values cannot contain nul bytes"
+        );
+
+        assert!(Format::Docker
+            .write(
+                BTreeMap::from([("A-B.C".to_owned(), String::new())])
+                    .into_iter()
+                    .collect()
+            )
+            .is_ok());
+    }
+}
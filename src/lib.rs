@@ -56,9 +56,30 @@
 //! If the `truthy-falsy` feature is disabled, only `true` and `false` are
 //! considered valid booleans.
 //!
+//! The feature only sets the default, which can be overridden at runtime with
+//! [`Options::strict_bools`], [`Options::lenient_bools`] or custom values
+//! ([`Options::truthy`] and [`Options::falsy`]):
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     cache: bool,
+//! }
+//!
+//! # std::env::set_var("CACHE", "enabled");
+//! let config: Config = de_env::Options::new()
+//!     .truthy(["enabled"])
+//!     .falsy(["disabled"])
+//!     .from_env()?;
+//!
+//! assert!(config.cache);
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
 //! ## Enums
 //!
-//! **Only unit variants can be deserialized.**
+//! The variable holds the variant name.
 //!
 //! Assuming we have a `LOG_LEVEL` environment variable set to `INFO` or `WARN`:
 //!
@@ -83,17 +104,548 @@
 //! # Ok::<(), de_env::Error>(())
 //! ```
 //!
+//! The payload of other variants is either:
+//!
+//! - Part of the value, following the variant name and a colon (e.g. `LOCAL:/var/lib/app`).
+//! - [Nested](#nested-structs) under the variable (e.g. `STORAGE=S3` and `STORAGE__BUCKET=app`).
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! enum Storage {
+//!     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//!     S3 { bucket: String },
+//!     Local(std::path::PathBuf),
+//! }
+//!
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     storage: Storage,
+//! }
+//!
+//! let options = de_env::Options::new().separator("__");
+//!
+//! let vars = [("STORAGE", "S3"), ("STORAGE__BUCKET", "app")];
+//! let config: Config = options.from_iter(vars.into_iter())?;
+//! assert!(matches!(config.storage, Storage::S3 { .. }));
+//!
+//! let vars = [("STORAGE", "LOCAL:/var/lib/app")];
+//! let config: Config = options.from_iter(vars.into_iter())?;
+//! assert!(matches!(config.storage, Storage::Local(_)));
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! ## Sequences
+//!
+//! Sequences (such as [`Vec`], [`HashSet`](std::collections::HashSet) or arrays) are
+//! deserialized by splitting the value on a [delimiter](Options::delimiter), `,` by default. Each
+//! element is parsed as any other value, an empty value is an empty sequence.
+//!
+//! Assuming we have an `ALLOWED_HOSTS` environment variable set to `127.0.0.1,::1`:
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     allowed_hosts: Vec<std::net::IpAddr>,
+//! }
+//!
+//! # std::env::set_var("ALLOWED_HOSTS", "127.0.0.1,::1");
+//! let config: Config = de_env::from_env()?;
+//!
+//! println!("{config:#?}");
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! Elements containing the delimiter can either:
+//!
+//! - Escape it with a backslash, e.g. `a\,b,c` is `["a,b", "c"]`.
+//! - Be enclosed in double quotes, e.g. `"a,b",c` is `["a,b", "c"]`. The closing quote must be
+//!   followed by the delimiter or the end of the value.
+//!
+//! Backslashes and double quotes can also be escaped with a backslash, any other backslash is
+//! kept as-is (e.g. `^\d+$` is `["^\d+$"]`). A double quote only starts a quoted element at the
+//! beginning of that element.
+//!
+//! ## Nested Structs
+//!
+//! Struct fields can themselves be structs once a [separator](Options::separator) is set, their
+//! fields are read from the variables prefixed with the field name and the separator.
+//!
+//! Assuming we have a `DATABASE__HOST` and `DATABASE__PORT` environment variable:
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Database {
+//!     host: std::net::IpAddr,
+//!     port: u16,
+//! }
+//!
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     database: Database,
+//! }
+//!
+//! # std::env::set_var("DATABASE__HOST", "127.0.0.1");
+//! # std::env::set_var("DATABASE__PORT", "5432");
+//! let config: Config = de_env::Options::new().separator("__").from_env()?;
+//!
+//! println!("{config:#?}");
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! Structs can be nested as deep as needed, a variable named exactly after a field always takes
+//! precedence over nesting. A struct field cannot have a variable of its own (e.g. `DATABASE`
+//! alongside `DATABASE__HOST`), as it is only read from its nested variables.
+//!
+//! ### Sequences of Structs
+//!
+//! Sequence elements can also be nested under their index, which must start at zero and be
+//! contiguous. This takes precedence over [delimited sequences](#sequences).
+//!
+//! Assuming we have a `SERVERS_0_HOST`, `SERVERS_0_PORT`, `SERVERS_1_HOST` and `SERVERS_1_PORT`
+//! environment variable:
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Server {
+//!     host: std::net::IpAddr,
+//!     port: u16,
+//! }
+//!
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     servers: Vec<Server>,
+//! }
+//!
+//! # std::env::set_var("SERVERS_0_HOST", "10.0.0.1");
+//! # std::env::set_var("SERVERS_0_PORT", "8080");
+//! # std::env::set_var("SERVERS_1_HOST", "10.0.0.2");
+//! # std::env::set_var("SERVERS_1_PORT", "8081");
+//! let config: Config = de_env::Options::new().separator("_").from_env()?;
+//!
+//! println!("{config:#?}");
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! ### Maps
+//!
+//! Maps (such as [`HashMap`](std::collections::HashMap) or
+//! [`BTreeMap`](std::collections::BTreeMap)) are deserialized from every variable nested under
+//! the field, keys are stripped of the field name and separator.
+//!
+//! Assuming we have a `LABEL_REGION` and `LABEL_ZONE` environment variable:
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     #[serde(default)]
+//!     label: std::collections::HashMap<String, String>,
+//! }
+//!
+//! # std::env::set_var("LABEL_REGION", "eu-west");
+//! # std::env::set_var("LABEL_ZONE", "b");
+//! let config: Config = de_env::Options::new().separator("_").from_env()?;
+//!
+//! assert_eq!(config.label["REGION"], "eu-west");
+//! assert_eq!(config.label["ZONE"], "b");
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! The map is considered missing when no variable is nested under the field, use
+//! `#[serde(default)]` to get an empty map instead.
+//!
+//! ## Top-level Maps
+//!
+//! The environment can also be deserialized into a map, each variable being an entry.
+//!
+//! Assuming we have a `PORT_HTTP` and `PORT_HTTPS` environment variable:
+//!
+//! ```rust
+//! use std::collections::BTreeMap;
+//!
+//! # std::env::set_var("PORT_HTTP", "80");
+//! # std::env::set_var("PORT_HTTPS", "443");
+//! let ports: BTreeMap<String, u16> = de_env::from_env_prefixed("PORT_")?;
+//!
+//! assert_eq!(ports["HTTP"], 80);
+//! assert_eq!(ports["HTTPS"], 443);
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! ## Flattening
+//!
+//! `#[serde(flatten)]` is supported, for both structs and catch-all maps.
+//!
+//! Assuming we have a `LOG_LEVEL`, `PORT` and `REGION` environment variable:
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Logging {
+//!     log_level: String,
+//! }
+//!
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     port: u16,
+//!     #[serde(flatten)]
+//!     logging: Logging,
+//!     #[serde(flatten)]
+//!     other: std::collections::HashMap<String, String>,
+//! }
+//!
+//! # let vars = [("LOG_LEVEL", "info"), ("PORT", "8080"), ("REGION", "eu-west")];
+//! let config: Config = de_env::from_iter(vars.into_iter())?;
+//!
+//! assert_eq!(config.logging.log_level, "info");
+//! assert_eq!(config.other["REGION"], "eu-west");
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! Serde buffers the variables that are not fields of the outer struct before handing them to the
+//! flattened fields, at which point they are strings unless [type inference](#type-inference) is
+//! enabled. This means fields of flattened structs must be deserializable from a string (or from
+//! the inferred type), and a struct containing a flattened field is deserialized as a
+//! [top-level map](#top-level-maps), so its fields cannot be [nested](#nested-structs).
+//!
+//! ## Type Inference
+//!
+//! Self-describing types (such as `#[serde(untagged)]` enums) do not tell which type they expect,
+//! values are then provided as strings. [`Options::infer_types`] makes values be provided as the
+//! first of the following types they can be parsed as:
+//!
+//! 1. Booleans (`true` or `false`, case-insensitive)
+//! 2. Integers
+//! 3. Floats (finite only)
+//!
+//! The order can be customized with [`Options::inference_order`], values matching none of the
+//! types are still provided as strings.
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(untagged)]
+//! enum Port {
+//!     Number(u16),
+//!     Named(String),
+//! }
+//!
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     port: Port,
+//! }
+//!
+//! # std::env::set_var("PORT", "8080");
+//! let config: Config = de_env::Options::new().infer_types().from_env()?;
+//!
+//! assert!(matches!(config.port, Port::Number(8080)));
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! ## Tagged Enums
+//!
+//! [Internally tagged] enums are read from a variable holding the variant name along with the
+//! variables holding its fields, either at top level or [nested](#nested-structs) under a field.
+//! Like [flattened](#flattening) fields, they are buffered by serde: their fields must be
+//! deserializable from a string (or from the [inferred type](#type-inference)) and cannot be
+//! nested.
+//!
+//! Assuming we have a `DB_KIND`, `DB_HOST` and `DB_PORT` environment variable:
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(tag = "KIND", rename_all = "lowercase")]
+//! enum Database {
+//!     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//!     Postgres { host: String, port: u16 },
+//!     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//!     Sqlite { path: std::path::PathBuf },
+//! }
+//!
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     db: Database,
+//! }
+//!
+//! # std::env::set_var("DB_KIND", "postgres");
+//! # std::env::set_var("DB_HOST", "localhost");
+//! # std::env::set_var("DB_PORT", "5432");
+//! let config: Config = de_env::Options::new()
+//!     .separator("_")
+//!     .infer_types()
+//!     .from_env()?;
+//!
+//! assert!(matches!(config.db, Database::Postgres { port: 5432, .. }));
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! [Adjacently tagged] enums are not buffered as the tag is always visited before the content,
+//! which is [nested](#nested-structs) under its own field (e.g. `DB_KIND` and `DB_CONFIG_HOST`).
+//!
+//! [Internally tagged]: https://serde.rs/enum-representations.html#internally-tagged
+//! [Adjacently tagged]: https://serde.rs/enum-representations.html#adjacently-tagged
+//!
+//! ## Dotenv Files
+//!
+//! Variables may be read from a dotenv file ([`from_dotenv_str`], [`from_dotenv_path`],
+//! [`from_dotenv_reader`] and the matching [`Options`] methods) rather than from the environment,
+//! which is left untouched. Each line holds a `KEY=value` pair, optionally prefixed with `export`:
+//!
+//! ```text
+//! # Comments span the rest of the line
+//! export HOST=localhost   # Unquoted values are trimmed
+//! GREETING='Taken $literally'
+//! MOTD="Escapes: \n \t \" \\ \$
+//! and multiple lines"
+//! ```
+//!
+//! Single-quoted values are taken literally, they are not [interpolated](#interpolation) either.
+//! In double-quoted values `\n`, `\r`, `\t`, `\\`, `\"` and `\$` (a literal `$`) are escape
+//! sequences, any other backslash is kept as-is. Malformed files are reported with the line and
+//! column of the error.
+//!
+//! ## Interpolation
+//!
+//! With [`Options::interpolate`], values may reference other variables of the same source (the
+//! environment, the iterator given to [`Options::from_iter`] or a dotenv file), whether or not
+//! they match the [prefix](Options::prefix):
+//!
+//! | Syntax            | Replaced by                                                        |
+//! |-------------------|--------------------------------------------------------------------|
+//! | `${VAR}`          | The value of `VAR`, or nothing if unset                            |
+//! | `${VAR:-default}` | The value of `VAR`, or `default` if unset or empty                 |
+//! | `${VAR:?message}` | The value of `VAR`, or an error with `message` if unset or empty   |
+//! | `$$`              | A literal `$`                                                      |
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     database_url: String,
+//! }
+//!
+//! let config: Config = de_env::Options::new()
+//!     .prefix("APP_")
+//!     .interpolate()
+//!     .from_iter(
+//!         [
+//!             ("USER", "admin"),
+//!             ("APP_DATABASE_URL", "postgres://${USER}@${HOST:-localhost}:${PORT:-5432}"),
+//!         ]
+//!         .into_iter(),
+//!     )?;
+//!
+//! assert_eq!(config.database_url, "postgres://admin@localhost:5432");
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! Referenced values are interpolated too, a reference cycle is an error naming the variables
+//! involved (e.g. ``reference cycle: `A` -> `B` -> `A` ``). Values are only interpolated when
+//! deserialized, so unrelated variables are never rejected. `$VAR` without braces is kept as-is.
+//! An interpolated value longer than 1 MiB is an error, so that references repeating large values
+//! cannot exhaust memory.
+//!
+//! ## Collecting Errors
+//!
+//! By default, deserialization stops at the first error. With [`Options::collect_errors`], it
+//! keeps going and reports every missing field and invalid value at once, each error naming its
+//! variable (see [`Error::errors`]).
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     timeout: u16,
+//!     host: std::net::IpAddr,
+//! }
+//!
+//! let error = de_env::Options::new()
+//!     .collect_errors()
+//!     .from_iter::<Config>([("TIMEOUT", "soon")].into_iter())
+//!     .unwrap_err();
+//!
+//! assert_eq!(
+//!     error.to_string(),
+//!     "2 errors occurred:
+//! - `TIMEOUT`: invalid digit found in string
+//! - `HOST`: missing field `HOST`"
+//! );
+//! ```
+//!
+//! This is done by deserializing again after each error, leaving the faulty variable out so that
+//! the remaining variables are checked. As a required field left out is reported missing, it is
+//! then replaced by a placeholder (one, an empty string or sequence, `None`, the first variant of
+//! an enum, ...) so that the fields declared after it are checked too. Missing fields declared
+//! after a field whose type rejects its placeholder (e.g. a URL, which cannot be empty) are not
+//! reported.
+//!
+//! ## Secrets
+//!
+//! [`Options::redaction`] keeps values out of errors:
+//!
+//! ```rust
+//! use de_env::Redaction;
+//!
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     api_token: u64,
+//! }
+//!
+//! let error = de_env::Options::new()
+//!     .redaction(Redaction::keys(["*_TOKEN"]))
+//!     .from_iter::<Config>([("API_TOKEN", "hunter2")].into_iter())
+//!     .unwrap_err();
+//!
+//! assert!(!error.to_string().contains("hunter2"));
+//! assert!(!format!("{error:?}").contains("hunter2"));
+//! ```
+//!
+//! If the `zeroize` feature is enabled, wrapping a field in `Secret` also keeps it out of `Debug`
+//! and `Display` output and wipes it from memory when dropped.
+//!
+//! ## Serialization
+//!
+//! Structs and maps can be turned back into variables ([`to_vec`], [`to_iter`], the matching
+//! [`Options`] methods and [`Serializer`]), e.g. to pass a config to a child process with
+//! [`apply_to_command`], or to write a file in one of the supported [formats](Format) with
+//! [`to_string`] or [`to_writer`]. The same
+//! options and rules apply, so that deserializing the variables gives back the original value:
+//!
+//! - Booleans are written as the first [truthy](Options::truthy) or [falsy](Options::falsy) value.
+//! - `None` is omitted, so `Some` of a value without variables (e.g. an empty map) is an error.
+//! - Sequences are delimited, with elements escaped as needed, unless an element has nested
+//!   variables, in which case they are indexed (e.g. `SERVERS_0_HOST`).
+//! - Enums are written as the variant name, their payload following it (`VARIANT:payload`) or
+//!   nested under the variable.
+//! - Nested structs and maps require a [separator](Options::separator). One without variables
+//!   (e.g. an empty map) is omitted, so its field is only read back with `#[serde(default)]`.
+//! - `$` is escaped as `$$` if [interpolation](#interpolation) is enabled.
+//!
+//! ```rust
+//! #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Database {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     debug: bool,
+//!     database: Database,
+//! }
+//!
+//! let options = de_env::Options::new().prefix("APP_").separator("__");
+//!
+//! let config = Config {
+//!     debug: false,
+//!     database: Database {
+//!         host: "localhost".to_owned(),
+//!         port: 5432,
+//!     },
+//! };
+//!
+//! let vars = options.to_vec(&config)?;
+//!
+//! assert_eq!(
+//!     vars,
+//!     [
+//!         ("APP_DEBUG".to_owned(), "false".to_owned()),
+//!         ("APP_DATABASE__HOST".to_owned(), "localhost".to_owned()),
+//!         ("APP_DATABASE__PORT".to_owned(), "5432".to_owned()),
+//!     ]
+//! );
+//! assert_eq!(options.from_iter::<Config>(vars.into_iter())?, config);
+//! # Ok::<(), de_env::Error>(())
+//! ```
+//!
+//! ## Describing Types
+//!
+//! [`describe`] (or [`Options::describe`]) lists the variables a type is deserialized from,
+//! without reading any, by tracing its `Deserialize` implementation. Each [`Variable`] has its
+//! name (with the prefix and separators applied), its type, whether it is required (i.e. not an
+//! `Option`) and the variants of enums. The [`Description`] renders to a Markdown table or to a
+//! `.env.example` template:
+//!
+//! ```rust
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Database {
+//!     host: String,
+//!     port: Option<u16>,
+//! }
+//!
+//! #[derive(serde::Deserialize, Debug)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct Config {
+//!     debug: bool,
+//!     database: Database,
+//! }
+//!
+//! let description = de_env::Options::new()
+//!     .prefix("APP_")
+//!     .separator("__")
+//!     .describe::<Config>();
+//!
+//! assert_eq!(
+//!     description.to_env_example(),
+//!     "\
+//! ## bool
+//! APP_DEBUG=
+//!
+//! ## string
+//! APP_DATABASE__HOST=
+//!
+//! ## u16 (optional)
+//! ## APP_DATABASE__PORT=
+//! "
+//! );
+//! ```
+//!
+//! Only what reaches the deserializer is known: types parsed from a string (e.g. `IpAddr`) are
+//! described as `string`, fields with a `#[serde(default)]` attribute as required, only the
+//! payload of the first variant of an enum is described, and variables that cannot be traced are
+//! described as `unsupported` rather than left out. These include flattened fields, internally
+//! tagged or untagged enums, recursive fields (e.g. `next: Option<Box<Node>>` in `Node`) and
+//! nested variables without a separator.
+//!
 //! ## Unsupported Types
 //!
-//! The goal of this crate is to deserialize environment variables into a **struct**, no other type
-//! is supported at top level. Custom types must be able to deserialize from [supported primitives].
+//! The goal of this crate is to deserialize environment variables into a **struct** or a **map**,
+//! no other type is supported at top level. Custom types must be able to deserialize from
+//! [supported primitives]. The same goes for [serialization](#serialization).
 //!
 //! [supported primitives]: #supported-primitives
 
 mod de;
+mod describe;
+mod dotenv;
 mod error;
+mod format;
+mod options;
+#[cfg(feature = "zeroize")]
+mod secret;
+mod ser;
 #[cfg(test)]
 mod tests;
 
-pub use de::{from_env, from_env_prefixed, from_iter};
-pub use error::{Error, Result};
+pub use de::{from_dir, from_env, from_env_prefixed, from_iter, Deserializer};
+pub use describe::{describe, Description, Variable};
+pub use dotenv::{from_dotenv_path, from_dotenv_reader, from_dotenv_str};
+pub use error::{Error, ErrorKind, Result};
+pub use format::{to_string, to_writer, Format};
+pub use options::{InferredType, Options, Redaction};
+#[cfg(feature = "zeroize")]
+pub use secret::Secret;
+pub use ser::{apply_to_command, to_iter, to_vec, Serializer};
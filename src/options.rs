@@ -0,0 +1,644 @@
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
+    path::Path,
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{Key, Value},
+    dotenv, Description, Deserializer, Error, Format, Result, Serializer,
+};
+
+/// Options used by the free functions ([`from_env`](crate::from_env),
+/// [`from_iter`](crate::from_iter), ...).
+pub(crate) static DEFAULT_OPTIONS: Options = Options::new();
+
+const STRICT_TRUTHY: &[Cow<'static, str>] = &[Cow::Borrowed("true")];
+
+const STRICT_FALSY: &[Cow<'static, str>] = &[Cow::Borrowed("false")];
+
+const LENIENT_TRUTHY: &[Cow<'static, str>] = &[
+    Cow::Borrowed("true"),
+    Cow::Borrowed("t"),
+    Cow::Borrowed("yes"),
+    Cow::Borrowed("y"),
+    Cow::Borrowed("on"),
+    Cow::Borrowed("1"),
+];
+
+const LENIENT_FALSY: &[Cow<'static, str>] = &[
+    Cow::Borrowed("false"),
+    Cow::Borrowed("f"),
+    Cow::Borrowed("no"),
+    Cow::Borrowed("n"),
+    Cow::Borrowed("off"),
+    Cow::Borrowed("0"),
+];
+
+/// Customize how environment variables are deserialized.
+///
+/// # Example
+///
+/// Assuming we have a `APP_DATABASE__HOST` and `APP_DATABASE__PORT` environment variable:
+///
+/// ```rust
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Database {
+///     host: std::net::IpAddr,
+///     port: u16,
+/// }
+///
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     database: Database,
+/// }
+///
+/// # std::env::set_var("APP_DATABASE__HOST", "127.0.0.1");
+/// # std::env::set_var("APP_DATABASE__PORT", "5432");
+/// let config: Config = de_env::Options::new()
+///     .prefix("APP_")
+///     .separator("__")
+///     .from_env()?;
+///
+/// println!("{config:#?}");
+/// # Ok::<(), de_env::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub(crate) prefix: Option<Cow<'static, str>>,
+    pub(crate) separator: Option<Cow<'static, str>>,
+    pub(crate) delimiter: Cow<'static, str>,
+    pub(crate) inference: Cow<'static, [InferredType]>,
+    pub(crate) truthy: Cow<'static, [Cow<'static, str>]>,
+    pub(crate) falsy: Cow<'static, [Cow<'static, str>]>,
+    pub(crate) collect_errors: bool,
+    pub(crate) redaction: Redaction,
+    pub(crate) file_suffix: Option<Cow<'static, str>>,
+    pub(crate) reject_subdirectories: bool,
+    pub(crate) interpolate: bool,
+    pub(crate) clear_env: bool,
+}
+
+/// A type that values may be inferred as, see [type inference].
+///
+/// [type inference]: crate#type-inference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InferredType {
+    /// `true` or `false`, case-insensitive.
+    Bool,
+    /// Unsigned or signed integers.
+    Integer,
+    /// Finite floating point numbers.
+    Float,
+}
+
+/// Which values are masked in errors, see [`Options::redaction`].
+///
+/// A redacted value is not returned by [`Error::value`](crate::Error::value), and error messages
+/// that may contain it (e.g. those of `Deserialize` implementations) are replaced by
+/// ``invalid value `[REDACTED]` ``. A value is also redacted if it [interpolates] a redacted
+/// variable.
+///
+/// [interpolates]: crate#interpolation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Redaction {
+    /// Do not mask any value.
+    Never,
+    /// Mask every value.
+    Always,
+    /// Mask the values of the variables whose name matches one of these patterns.
+    ///
+    /// Patterns are matched against the whole variable name (including the prefix, if any), `*`
+    /// matches any sequence of characters, e.g. `*_TOKEN` or `DATABASE_*`.
+    Keys(Cow<'static, [Cow<'static, str>]>),
+}
+
+impl Redaction {
+    /// Mask the values of the variables whose name matches one of `patterns`, see
+    /// [`Redaction::Keys`].
+    pub fn keys<S>(patterns: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        Self::Keys(patterns.into_iter().map(Into::into).collect())
+    }
+
+    /// Whether the value of the variable named `key` should be masked.
+    pub(crate) fn applies(&self, key: &str) -> bool {
+        match self {
+            Self::Never => false,
+            Self::Always => true,
+            Self::Keys(patterns) => patterns.iter().any(|pattern| matches(pattern, key)),
+        }
+    }
+}
+
+/// Whether `key` matches `pattern`, where `*` matches any sequence of characters.
+fn matches(pattern: &str, key: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+
+    let Some(mut rest) = key.strip_prefix(first) else {
+        return false;
+    };
+
+    let Some(last) = parts.next_back() else {
+        // No wildcard.
+        return rest.is_empty();
+    };
+
+    for part in parts {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+impl Options {
+    /// Create the default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            prefix: None,
+            separator: None,
+            delimiter: Cow::Borrowed(","),
+            inference: Cow::Borrowed(&[]),
+            #[cfg(feature = "truthy-falsy")]
+            truthy: Cow::Borrowed(LENIENT_TRUTHY),
+            #[cfg(feature = "truthy-falsy")]
+            falsy: Cow::Borrowed(LENIENT_FALSY),
+            #[cfg(not(feature = "truthy-falsy"))]
+            truthy: Cow::Borrowed(STRICT_TRUTHY),
+            #[cfg(not(feature = "truthy-falsy"))]
+            falsy: Cow::Borrowed(STRICT_FALSY),
+            collect_errors: false,
+            redaction: Redaction::Never,
+            file_suffix: None,
+            reject_subdirectories: false,
+            interpolate: false,
+            clear_env: false,
+        }
+    }
+
+    /// Only read the variables starting with `prefix`, stripped of it.
+    ///
+    /// No prefix is set by default.
+    #[must_use]
+    pub fn prefix(mut self, prefix: impl Into<Cow<'static, str>>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set the separator used to deserialize [nested structs].
+    ///
+    /// No separator is set by default, in which case nested structs cannot be deserialized.
+    ///
+    /// [nested structs]: crate#nested-structs
+    #[must_use]
+    pub fn separator(mut self, separator: impl Into<Cow<'static, str>>) -> Self {
+        self.separator = Some(separator.into());
+        self
+    }
+
+    /// Set the delimiter used to split [sequences].
+    ///
+    /// Defaults to `,`.
+    ///
+    /// # Panics
+    /// Panics if `delimiter` is empty.
+    ///
+    /// [sequences]: crate#sequences
+    #[must_use]
+    pub fn delimiter(mut self, delimiter: impl Into<Cow<'static, str>>) -> Self {
+        self.delimiter = delimiter.into();
+        assert!(
+            !self.delimiter.is_empty(),
+            "the delimiter must not be empty"
+        );
+        self
+    }
+
+    /// Set the values parsed as `true`, see [boolean parsing].
+    ///
+    /// [boolean parsing]: crate#boolean-parsing
+    #[must_use]
+    pub fn truthy<S>(mut self, values: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        self.truthy = values.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the values parsed as `false`, see [boolean parsing].
+    ///
+    /// [boolean parsing]: crate#boolean-parsing
+    #[must_use]
+    pub fn falsy<S>(mut self, values: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        self.falsy = values.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only parse `true` and `false` as booleans, whether the `truthy-falsy` feature is enabled or
+    /// not.
+    #[must_use]
+    pub fn strict_bools(mut self) -> Self {
+        self.truthy = Cow::Borrowed(STRICT_TRUTHY);
+        self.falsy = Cow::Borrowed(STRICT_FALSY);
+        self
+    }
+
+    /// Parse the values listed in [boolean parsing] as booleans, whether the `truthy-falsy` feature
+    /// is enabled or not.
+    ///
+    /// [boolean parsing]: crate#boolean-parsing
+    #[must_use]
+    pub fn lenient_bools(mut self) -> Self {
+        self.truthy = Cow::Borrowed(LENIENT_TRUTHY);
+        self.falsy = Cow::Borrowed(LENIENT_FALSY);
+        self
+    }
+
+    /// Enable [type inference], trying booleans, then integers, then floats.
+    ///
+    /// [type inference]: crate#type-inference
+    #[must_use]
+    pub fn infer_types(self) -> Self {
+        self.inference_order(
+            &[
+                InferredType::Bool,
+                InferredType::Integer,
+                InferredType::Float,
+            ][..],
+        )
+    }
+
+    /// Set the types tried, in order, by [type inference].
+    ///
+    /// Type inference is disabled by default (i.e. the order is empty).
+    ///
+    /// [type inference]: crate#type-inference
+    #[must_use]
+    pub fn inference_order(mut self, order: impl Into<Cow<'static, [InferredType]>>) -> Self {
+        self.inference = order.into();
+        self
+    }
+
+    /// Enable [interpolation], replacing references to other variables (e.g. `${HOST}`) in values.
+    ///
+    /// [interpolation]: crate#interpolation
+    #[must_use]
+    pub fn interpolate(mut self) -> Self {
+        self.interpolate = true;
+        self
+    }
+
+    /// Read the value of a struct field from a file if the `<FIELD><suffix>` variable (e.g.
+    /// `PASSWORD_FILE` for a `_FILE` suffix) names it and the `<FIELD>` variable is not set.
+    ///
+    /// A trailing newline is trimmed from the contents of the file, which must be valid unicode.
+    ///
+    /// No suffix is set by default, in which case no file is read.
+    ///
+    /// # Example
+    ///
+    /// Assuming we have a `DB_PASSWORD_FILE` environment variable holding the path of a file:
+    ///
+    /// ```rust
+    /// #[derive(serde::Deserialize, Debug)]
+    /// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    /// struct Config {
+    ///     db_password: String,
+    /// }
+    ///
+    /// # let path = std::env::temp_dir().join("de_env_file_suffix_example");
+    /// # std::fs::write(&path, "hunter2\n").unwrap();
+    /// # std::env::set_var("DB_PASSWORD_FILE", &path);
+    /// let config: Config = de_env::Options::new().file_suffix("_FILE").from_env()?;
+    ///
+    /// assert_eq!(config.db_password, "hunter2");
+    /// # Ok::<(), de_env::Error>(())
+    /// ```
+    #[must_use]
+    pub fn file_suffix(mut self, suffix: impl Into<Cow<'static, str>>) -> Self {
+        self.file_suffix = Some(suffix.into());
+        self
+    }
+
+    /// Fail if the directory read by [`Options::from_dir`] holds a subdirectory (other than a
+    /// hidden one).
+    ///
+    /// Subdirectories are skipped by default.
+    #[must_use]
+    pub fn reject_subdirectories(mut self) -> Self {
+        self.reject_subdirectories = true;
+        self
+    }
+
+    /// Clear the environment of the command given to [`Options::apply_to_command`] first, so
+    /// that it only inherits the serialized variables.
+    ///
+    /// The environment is inherited by default.
+    #[must_use]
+    pub fn clear_env(mut self) -> Self {
+        self.clear_env = true;
+        self
+    }
+
+    /// Keep going after an error and report every error at once, see [collecting errors].
+    ///
+    /// Only the first error is reported by default.
+    ///
+    /// [collecting errors]: crate#collecting-errors
+    #[must_use]
+    pub fn collect_errors(mut self) -> Self {
+        self.collect_errors = true;
+        self
+    }
+
+    /// Set which values are masked in errors.
+    ///
+    /// No value is masked by default (i.e. [`Redaction::Never`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use de_env::{Options, Redaction};
+    ///
+    /// let options = Options::new().redaction(Redaction::keys(["*_TOKEN", "*_PASSWORD"]));
+    /// ```
+    #[must_use]
+    pub fn redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Deserialize an instance of `T` from the environment variables of the current process.
+    ///
+    /// See [`from_env`](crate::from_env), or [`Deserializer::from_env`] to get a
+    /// [`serde::Deserializer`].
+    ///
+    /// # Errors
+    /// This conversion can fail if trying to deserialize [unsupported types], or if `T`'s
+    /// implementation of `Deserialize` decides that something is wrong with the data.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn from_env<'de, T>(&'de self) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        Deserializer::from_env(self).deserialize()
+    }
+
+    /// Deserialize an instance of `T` from an iterator of key-value tuple.
+    ///
+    /// See [`from_iter`](crate::from_iter), or [`Deserializer::from_iter`] to get a
+    /// [`serde::Deserializer`].
+    ///
+    /// # Errors
+    /// This conversion can fail if trying to deserialize [unsupported types], or if `T`'s
+    /// implementation of `Deserialize` decides that something is wrong with the data.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn from_iter<'de, T>(
+        &'de self,
+        iter: impl Iterator<Item = (impl Into<Key<'de>>, impl Into<Value<'de>>)>,
+    ) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        Deserializer::from_iter(self, iter).deserialize()
+    }
+
+    /// Deserialize an instance of `T` from a directory holding one file per variable.
+    ///
+    /// See [`from_dir`](crate::from_dir), or [`Deserializer::from_dir`] to get a
+    /// [`serde::Deserializer`].
+    ///
+    /// # Errors
+    /// This conversion can fail if the directory or one of its files cannot be read, if trying to
+    /// deserialize [unsupported types], or if `T`'s implementation of `Deserialize` decides that
+    /// something is wrong with the data.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn from_dir<'de, T>(&'de self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        Deserializer::from_dir(self, path)?.deserialize()
+    }
+
+    /// Deserialize an instance of `T` from the contents of a dotenv file.
+    ///
+    /// See [`from_dotenv_str`](crate::from_dotenv_str).
+    ///
+    /// # Errors
+    /// This conversion can fail if `input` is not a valid dotenv file, if trying to deserialize
+    /// [unsupported types], or if `T`'s implementation of `Deserialize` decides that something is
+    /// wrong with the data.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn from_dotenv_str<'de, T>(&'de self, input: &str) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        self.from_iter(dotenv::parse(input, self.interpolate)?.into_iter())
+    }
+
+    /// Deserialize an instance of `T` from the dotenv file at `path`.
+    ///
+    /// See [`from_dotenv_path`](crate::from_dotenv_path).
+    ///
+    /// # Errors
+    /// This conversion can fail if the file cannot be read or is not a valid dotenv file, if trying
+    /// to deserialize [unsupported types], or if `T`'s implementation of `Deserialize` decides that
+    /// something is wrong with the data.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn from_dotenv_path<'de, T>(&'de self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        let path = path.as_ref();
+        let input = std::fs::read_to_string(path)
+            .map_err(|error| Error::read_file(path.to_owned(), error))?;
+
+        self.from_dotenv_str(&input)
+    }
+
+    /// Deserialize an instance of `T` from a reader over the contents of a dotenv file.
+    ///
+    /// See [`from_dotenv_reader`](crate::from_dotenv_reader).
+    ///
+    /// # Errors
+    /// This conversion can fail if the reader fails or its contents are not a valid dotenv file, if
+    /// trying to deserialize [unsupported types], or if `T`'s implementation of `Deserialize`
+    /// decides that something is wrong with the data.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn from_dotenv_reader<'de, T>(&'de self, mut reader: impl Read) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        let mut input = String::new();
+        reader.read_to_string(&mut input).map_err(Error::io)?;
+
+        self.from_dotenv_str(&input)
+    }
+
+    /// Serialize `value` into environment variables.
+    ///
+    /// See [`to_vec`](crate::to_vec), or [`Serializer::new`] to get a [`serde::Serializer`].
+    ///
+    /// # Errors
+    /// This conversion can fail if trying to serialize [unsupported types], or if `T`'s
+    /// implementation of `Serialize` decides to fail.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn to_vec<T>(&self, value: &T) -> Result<Vec<(String, String)>>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(Serializer::new(self))
+    }
+
+    /// Serialize `value` into an iterator of key-value tuple.
+    ///
+    /// See [`to_iter`](crate::to_iter).
+    ///
+    /// # Errors
+    /// This conversion can fail if trying to serialize [unsupported types], or if `T`'s
+    /// implementation of `Serialize` decides to fail.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn to_iter<T>(&self, value: &T) -> Result<impl Iterator<Item = (String, String)>>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.to_vec(value).map(Vec::into_iter)
+    }
+
+    /// Serialize `value` into a file in the given `format`.
+    ///
+    /// See [`to_string`](crate::to_string).
+    ///
+    /// # Errors
+    /// This conversion can fail if trying to serialize [unsupported types], if `T`'s
+    /// implementation of `Serialize` decides to fail, or if a variable cannot be written in
+    /// `format`.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn to_string<T>(&self, value: &T, format: Format) -> Result<String>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut vars = self.to_vec(value)?;
+
+        // `$` is escaped as `\$` in dotenv files, which is read back as a literal `$` when
+        // interpolating, so the `$$` escape is not needed.
+        if self.interpolate && format == Format::Dotenv {
+            for (_, value) in &mut vars {
+                *value = value.replace("$$", "$");
+            }
+        }
+
+        format.write(vars)
+    }
+
+    /// Serialize `value` into a file in the given `format`, written to `writer`.
+    ///
+    /// See [`to_writer`](crate::to_writer).
+    ///
+    /// # Errors
+    /// This conversion can fail if trying to serialize [unsupported types], if `T`'s
+    /// implementation of `Serialize` decides to fail, if a variable cannot be written in `format`
+    /// or if the writer fails.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn to_writer<T>(&self, value: &T, format: Format, mut writer: impl Write) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let output = self.to_string(value, format)?;
+
+        writer.write_all(output.as_bytes()).map_err(Error::io)
+    }
+
+    /// Serialize `value` into the environment of `command`.
+    ///
+    /// See [`apply_to_command`](crate::apply_to_command).
+    ///
+    /// # Errors
+    /// This conversion can fail if trying to serialize [unsupported types], or if `T`'s
+    /// implementation of `Serialize` decides to fail, in which case `command` is left untouched.
+    ///
+    /// [unsupported types]: crate#unsupported-types
+    pub fn apply_to_command<T>(&self, value: &T, command: &mut Command) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let vars = self.to_vec(value)?;
+
+        if self.clear_env {
+            command.env_clear();
+        }
+
+        command.envs(vars);
+
+        Ok(())
+    }
+
+    /// Describe the variables `T` is deserialized from, using the prefix and separator of these
+    /// options.
+    ///
+    /// See [`describe`](crate::describe).
+    #[must_use]
+    pub fn describe<'de, T>(&self) -> Description
+    where
+        T: Deserialize<'de>,
+    {
+        Description::new::<T>(self)
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Redaction;
+
+    #[test]
+    fn redaction() {
+        let redaction = Redaction::keys(["*_TOKEN", "DATABASE_*", "KEY", "A*B*C"]);
+
+        assert!(redaction.applies("API_TOKEN"));
+        assert!(redaction.applies("_TOKEN"));
+        assert!(redaction.applies("DATABASE_PASSWORD"));
+        assert!(redaction.applies("KEY"));
+        assert!(redaction.applies("ABC"));
+        assert!(redaction.applies("AxxBxxC"));
+        assert!(!redaction.applies("API_TOKENS"));
+        assert!(!redaction.applies("DATABASE"));
+        assert!(!redaction.applies("KEYS"));
+        assert!(!redaction.applies("ACB"));
+        assert!(!Redaction::Never.applies("KEY"));
+        assert!(Redaction::Always.applies("KEY"));
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A value that is not printed and is wiped from memory when dropped.
+///
+/// `Secret<T>` deserializes and serializes like `T`, but its `Debug` and `Display`
+/// implementations print `[REDACTED]` so that it does not leak when printing a config. The value
+/// is only accessible through [`Secret::expose`].
+///
+/// Only the memory owned by the secret is wiped, not the environment it was read from nor the
+/// intermediate copies made while deserializing. See also [`Options::redaction`] to mask values in
+/// errors.
+///
+/// # Example
+///
+/// Assuming we have a `API_TOKEN` environment variable:
+///
+/// ```rust
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     api_token: de_env::Secret<String>,
+/// }
+///
+/// # std::env::set_var("API_TOKEN", "hunter2");
+/// let config: Config = de_env::from_env()?;
+///
+/// assert_eq!(format!("{config:?}"), "Config { api_token: [REDACTED] }");
+/// assert_eq!(config.api_token.expose(), "hunter2");
+/// # Ok::<(), de_env::Error>(())
+/// ```
+///
+/// [`Options::redaction`]: crate::Options::redaction
+#[derive(Clone)]
+pub struct Secret<T> {
+    value: T,
+    /// Wipe the value, set by the constructors so that the struct itself does not require `T` to
+    /// be [`Zeroize`].
+    wipe: fn(&mut T),
+}
+
+impl<T: Zeroize> Secret<T> {
+    /// Wrap `value`.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            wipe: T::zeroize,
+        }
+    }
+}
+
+impl<T> Secret<T> {
+    /// Access the value.
+    pub fn expose(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Zeroize + Default> Default for Secret<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// If `T` wipes itself when dropped (i.e. it is [`ZeroizeOnDrop`]), it is wiped twice, which is
+/// harmless.
+impl<T> Drop for Secret<T> {
+    fn drop(&mut self) {
+        (self.wipe)(&mut self.value);
+    }
+}
+
+impl<T> ZeroizeOnDrop for Secret<T> {}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("[REDACTED]")
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("[REDACTED]")
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Secret<T>
+where
+    T: Zeroize + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Serializes the value as-is, e.g. to pass it on to a child process with
+/// [`apply_to_command`](crate::apply_to_command).
+impl<T> Serialize for Secret<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Secret;
+
+    #[test]
+    fn redacted() {
+        let secret = Secret::new(String::from("hunter2"));
+
+        assert_eq!(format!("{secret:?}"), "[REDACTED]");
+        assert_eq!(format!("{secret:#?}"), "[REDACTED]");
+        assert_eq!(secret.to_string(), "[REDACTED]");
+        assert_eq!(secret.expose(), "hunter2");
+    }
+
+    #[test]
+    fn wiped() {
+        use std::{cell::Cell, rc::Rc};
+
+        use zeroize::Zeroize;
+
+        struct Tracked(Rc<Cell<usize>>);
+
+        impl Zeroize for Tracked {
+            fn zeroize(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let wiped = Rc::new(Cell::new(0));
+        let secret = Secret::new(Tracked(Rc::clone(&wiped)));
+
+        assert_eq!(wiped.get(), 0);
+        drop(secret);
+        assert_eq!(wiped.get(), 1);
+    }
+
+    #[test]
+    fn serialize() {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        struct Test {
+            token: Secret<String>,
+        }
+
+        let test = Test {
+            token: Secret::new(String::from("hunter2")),
+        };
+
+        assert_eq!(
+            crate::to_vec(&test).unwrap(),
+            [("TOKEN".to_owned(), "hunter2".to_owned())]
+        );
+    }
+
+    #[test]
+    fn deserialize() {
+        #[derive(serde::Deserialize, Debug)]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        struct Test {
+            token: Secret<String>,
+            pin: Option<Secret<u16>>,
+            keys: Secret<Vec<String>>,
+        }
+
+        let test: Test =
+            crate::from_iter([("TOKEN", "hunter2"), ("PIN", "1234"), ("KEYS", "a,b")].into_iter())
+                .unwrap();
+
+        assert_eq!(test.token.expose(), "hunter2");
+        assert_eq!(test.pin.as_ref().map(|pin| *pin.expose()), Some(1234));
+        assert_eq!(test.keys.expose(), &["a", "b"]);
+        assert_eq!(
+            format!("{test:?}"),
+            "Test { token: [REDACTED], pin: Some([REDACTED]), keys: [REDACTED] }"
+        );
+    }
+
+    #[test]
+    fn describe() {
+        #[allow(dead_code)]
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        struct Test {
+            token: Secret<String>,
+        }
+
+        let description = crate::describe::<Test>();
+        let variable = &description.variables()[0];
+
+        assert_eq!(variable.key(), "TOKEN");
+        assert_eq!(variable.ty(), "string");
+    }
+}
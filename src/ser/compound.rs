@@ -0,0 +1,300 @@
+use serde::Serialize;
+
+use crate::{de::Location, Error, Options, Result};
+
+use super::node::{self, Node};
+
+/// A struct, map or struct variant, whose fields are nested under it.
+pub struct Compound<'a> {
+    options: &'a Options,
+    /// Unknown for the top-level struct or map, whose fields do not require a separator.
+    location: Option<Location>,
+    /// The location the fields are nested under.
+    children: Location,
+    variant: Option<&'static str>,
+    nested: Vec<(String, String)>,
+    /// The key of the map entry whose value is being serialized.
+    key: Option<String>,
+}
+
+impl<'a> Compound<'a> {
+    pub fn new(
+        options: &'a Options,
+        location: Option<Location>,
+        children: Location,
+        variant: Option<&'static str>,
+    ) -> Self {
+        Self {
+            options,
+            location,
+            children,
+            variant,
+            nested: Vec::new(),
+            key: None,
+        }
+    }
+
+    fn push<T>(&mut self, segment: &str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let location = self.children.nested(segment);
+        let key = location.key.clone();
+
+        node::serialize(self.options, location, value)?.push_into(key, &mut self.nested);
+
+        Ok(())
+    }
+
+    /// The variables of a top-level struct or map.
+    pub fn into_entries(self) -> Result<Vec<(String, String)>> {
+        let options = self.options;
+
+        Ok(self.finish()?.into_entries(options))
+    }
+
+    pub fn finish(self) -> Result<Node> {
+        if let Some(location) = self.location {
+            if self.options.separator.is_none() && !self.nested.is_empty() {
+                return Err(Error::missing_separator().with_context(
+                    location.key,
+                    location.path,
+                    None,
+                    false,
+                ));
+            }
+        }
+
+        let node = Node {
+            value: None,
+            nested: self.nested,
+        };
+
+        match self.variant {
+            Some(variant) => node.into_variant(variant),
+            None => Ok(node),
+        }
+    }
+}
+
+impl serde::ser::SerializeStruct for Compound<'_> {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl serde::ser::SerializeStructVariant for Compound<'_> {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl serde::ser::SerializeMap for Compound<'_> {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(node::serialize_key(
+            self.options,
+            self.location.as_ref(),
+            key,
+        )?);
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self.key.take().unwrap_or_default();
+
+        self.push(&key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+/// A sequence, tuple or tuple variant, whose elements are either delimited or indexed, see
+/// [sequences].
+///
+/// [sequences]: crate#sequences
+pub struct Seq<'a> {
+    options: &'a Options,
+    location: Location,
+    variant: Option<&'static str>,
+    elements: Vec<(String, Node)>,
+}
+
+impl<'a> Seq<'a> {
+    pub fn new(options: &'a Options, location: Location, variant: Option<&'static str>) -> Self {
+        Self {
+            options,
+            location,
+            variant,
+            elements: Vec::new(),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let location =
+            node::nested(self.options, &self.location).nested(&self.elements.len().to_string());
+        let key = location.key.clone();
+        let path = location.path.clone();
+
+        let node = node::serialize(self.options, location, value)?;
+
+        // Neither delimited nor indexed elements can be missing.
+        if node.is_empty() {
+            return Err(Error::unserializable("none").with_context(key, path, None, false));
+        }
+
+        self.elements.push((key, node));
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Node> {
+        let node = if self.elements.iter().all(|(_, node)| node.is_scalar()) {
+            let delimiter = self.options.delimiter.as_ref();
+
+            let elements: Vec<String> = self
+                .elements
+                .into_iter()
+                .map(|(_, node)| escape(&node.value.unwrap_or_default(), delimiter))
+                .collect();
+
+            Node::scalar(elements.join(delimiter))
+        } else {
+            if self.options.separator.is_none() {
+                return Err(Error::missing_separator().with_context(
+                    self.location.key,
+                    self.location.path,
+                    None,
+                    false,
+                ));
+            }
+
+            let mut nested = Vec::new();
+
+            for (key, node) in self.elements {
+                node.push_into(key, &mut nested);
+            }
+
+            Node {
+                value: None,
+                nested,
+            }
+        };
+
+        match self.variant {
+            Some(variant) => node.into_variant(variant),
+            None => Ok(node),
+        }
+    }
+}
+
+/// Escape an element of a delimited sequence, see [sequences].
+///
+/// [sequences]: crate#sequences
+fn escape(element: &str, delimiter: &str) -> String {
+    // A single empty element would otherwise be an empty sequence.
+    if element.is_empty() {
+        return String::from(r#""""#);
+    }
+
+    element
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace(delimiter, &format!("\\{delimiter}"))
+}
+
+impl serde::ser::SerializeSeq for Seq<'_> {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl serde::ser::SerializeTuple for Seq<'_> {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl serde::ser::SerializeTupleVariant for Seq<'_> {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape;
+
+    #[test]
+    fn escaped() {
+        assert_eq!(escape("a", ","), "a");
+        assert_eq!(escape("", ","), r#""""#);
+        assert_eq!(escape("a,b", ","), r"a\,b");
+        assert_eq!(escape(r#""a"\"#, ","), r#"\"a\"\\"#);
+        assert_eq!(escape("a::b,c", "::"), r"a\::b,c");
+    }
+}
@@ -0,0 +1,321 @@
+use std::process::Command;
+
+use serde::{ser::Impossible, Serialize};
+
+use crate::{de::Location, options::DEFAULT_OPTIONS, Error, Options, Result};
+
+use self::compound::Compound;
+
+mod compound;
+mod node;
+
+/// Serialize `value` into environment variables, see [serialization].
+///
+/// # Example
+/// ```rust
+/// #[derive(serde::Serialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     timeout: u16,
+///     hosts: Vec<String>,
+///     proxy: Option<String>,
+/// }
+///
+/// let config = Config {
+///     timeout: 12,
+///     hosts: vec!["localhost".to_owned(), "127.0.0.1".to_owned()],
+///     proxy: None,
+/// };
+///
+/// assert_eq!(
+///     de_env::to_vec(&config)?,
+///     [
+///         ("TIMEOUT".to_owned(), "12".to_owned()),
+///         ("HOSTS".to_owned(), "localhost,127.0.0.1".to_owned()),
+///     ]
+/// );
+/// # Ok::<(), de_env::Error>(())
+/// ```
+///
+/// # Errors
+/// This conversion can fail if trying to serialize [unsupported types], or if `T`'s
+/// implementation of `Serialize` decides to fail.
+///
+/// [serialization]: crate#serialization
+/// [unsupported types]: crate#unsupported-types
+pub fn to_vec<T>(value: &T) -> Result<Vec<(String, String)>>
+where
+    T: Serialize + ?Sized,
+{
+    DEFAULT_OPTIONS.to_vec(value)
+}
+
+/// Serialize `value` into an iterator of key-value tuple, see [`to_vec`].
+///
+/// This is intended to be fed to functions expecting the same items as [`std::env::vars()`], such
+/// as [`std::process::Command::envs`].
+///
+/// # Errors
+/// This conversion can fail if trying to serialize [unsupported types], or if `T`'s
+/// implementation of `Serialize` decides to fail.
+///
+/// [unsupported types]: crate#unsupported-types
+pub fn to_iter<T>(value: &T) -> Result<impl Iterator<Item = (String, String)>>
+where
+    T: Serialize + ?Sized,
+{
+    DEFAULT_OPTIONS.to_iter(value)
+}
+
+/// Serialize `value` into the environment of `command`, see [serialization].
+///
+/// The variables are added to the environment the command inherits, use
+/// [`Options::clear_env`] to only pass the serialized variables, and [`Options::prefix`] to prefix
+/// their names.
+///
+/// # Example
+/// ```rust,no_run
+/// #[derive(serde::Serialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Worker {
+///     id: u16,
+///     queue: String,
+/// }
+///
+/// let worker = Worker {
+///     id: 1,
+///     queue: "jobs".to_owned(),
+/// };
+///
+/// let mut command = std::process::Command::new("worker");
+///
+/// de_env::Options::new()
+///     .prefix("WORKER_")
+///     .clear_env()
+///     .apply_to_command(&worker, &mut command)?;
+///
+/// // Only `WORKER_ID=1` and `WORKER_QUEUE=jobs` are set.
+/// command.spawn()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+/// This conversion can fail if trying to serialize [unsupported types], or if `T`'s
+/// implementation of `Serialize` decides to fail, in which case `command` is left untouched.
+///
+/// [serialization]: crate#serialization
+/// [unsupported types]: crate#unsupported-types
+pub fn apply_to_command<T>(value: &T, command: &mut Command) -> Result<()>
+where
+    T: Serialize + ?Sized,
+{
+    DEFAULT_OPTIONS.apply_to_command(value, command)
+}
+
+/// A [`serde::Serializer`] into environment variables, the reverse of [`Deserializer`].
+///
+/// This is intended to be used with serde tooling that expects a serializer, otherwise see
+/// [`Options`] or the free functions ([`to_vec`], [`to_iter`]).
+///
+/// # Example
+/// ```rust
+/// use serde::Serialize;
+///
+/// #[derive(serde::Serialize, Debug)]
+/// #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///     timeout: u16,
+/// }
+///
+/// let options = de_env::Options::new().prefix("APP_");
+/// let vars = Config { timeout: 12 }.serialize(de_env::Serializer::new(&options))?;
+///
+/// assert_eq!(vars, [("APP_TIMEOUT".to_owned(), "12".to_owned())]);
+/// # Ok::<(), de_env::Error>(())
+/// ```
+///
+/// [`Deserializer`]: crate::Deserializer
+pub struct Serializer<'a> {
+    options: &'a Options,
+}
+
+impl<'a> Serializer<'a> {
+    /// Create a serializer using the prefix, separator, delimiter and boolean values of `options`.
+    #[must_use]
+    pub fn new(options: &'a Options) -> Self {
+        Self { options }
+    }
+
+    fn compound(self, variant: Option<&'static str>) -> Compound<'a> {
+        let location = Location {
+            key: self
+                .options
+                .prefix
+                .as_deref()
+                .unwrap_or_default()
+                .to_owned(),
+            ..Location::default()
+        };
+
+        Compound::new(self.options, None, location, variant)
+    }
+}
+
+/// The top-level struct or map, see [`Serializer`].
+#[doc(hidden)]
+pub struct SerializeRoot<'a>(Compound<'a>);
+
+macro_rules! unsupported_types {
+    ($($ty:ident($($arg:ty),*))*) => {
+        paste::paste! {
+            $(
+                fn [<serialize_ $ty>](self, $(_: $arg),*) -> Result<Self::Ok> {
+                    Err(Error::unserializable(stringify!($ty)))
+                }
+            )*
+        }
+    };
+}
+
+pub(crate) use unsupported_types;
+
+impl<'a> serde::Serializer for Serializer<'a> {
+    type Ok = Vec<(String, String)>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Error>;
+    type SerializeTuple = Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Error>;
+    type SerializeMap = SerializeRoot<'a>;
+    type SerializeStruct = SerializeRoot<'a>;
+    type SerializeStructVariant = Impossible<Self::Ok, Error>;
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(SerializeRoot(self.compound(None)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(SerializeRoot(self.compound(None)))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(Error::unserializable("option"))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(Error::unserializable("enum"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::unserializable("seq"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::unserializable("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::unserializable("tuple_struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::unserializable("enum"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::unserializable("enum"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Err(Error::unserializable("enum"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Err(Error::unserializable("unit_struct"))
+    }
+
+    unsupported_types! {
+        bool(bool) i8(i8) i16(i16) i32(i32) i64(i64) i128(i128)
+        u8(u8) u16(u16) u32(u32) u64(u64) u128(u128) f32(f32) f64(f64)
+        char(char) str(&str) bytes(&[u8]) none() unit()
+    }
+}
+
+impl serde::ser::SerializeStruct for SerializeRoot<'_> {
+    type Ok = Vec<(String, String)>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        serde::ser::SerializeStruct::serialize_field(&mut self.0, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.0.into_entries()
+    }
+}
+
+impl serde::ser::SerializeMap for SerializeRoot<'_> {
+    type Ok = Vec<(String, String)>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        serde::ser::SerializeMap::serialize_key(&mut self.0, key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        serde::ser::SerializeMap::serialize_value(&mut self.0, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.0.into_entries()
+    }
+}
@@ -0,0 +1,298 @@
+use serde::{ser::Impossible, Serialize};
+
+use crate::{de::Location, Error, Options, Result};
+
+use super::compound::{Compound, Seq};
+
+/// A serialized struct field or sequence element: the value of the variable named after it (if
+/// any) and the variables nested under it.
+#[derive(Debug, Default)]
+pub struct Node {
+    pub value: Option<String>,
+    pub nested: Vec<(String, String)>,
+}
+
+impl Node {
+    pub fn scalar(value: String) -> Self {
+        Self {
+            value: Some(value),
+            nested: Vec::new(),
+        }
+    }
+
+    /// Whether this node is a single value, which may be an element of a delimited sequence.
+    pub fn is_scalar(&self) -> bool {
+        self.value.is_some() && self.nested.is_empty()
+    }
+
+    /// Whether this node produces no variable (e.g. `None`).
+    pub fn is_empty(&self) -> bool {
+        self.value.is_none() && self.nested.is_empty()
+    }
+
+    /// The variables of this node, named `key`.
+    pub fn push_into(self, key: String, entries: &mut Vec<(String, String)>) {
+        if let Some(value) = self.value {
+            entries.push((key, value));
+        }
+
+        entries.extend(self.nested);
+    }
+
+    /// The variables of a top-level node, which only has nested variables.
+    ///
+    /// If [interpolation] is enabled, `$` is escaped as `$$` so that values are read back as-is.
+    ///
+    /// [interpolation]: crate#interpolation
+    pub fn into_entries(self, options: &Options) -> Vec<(String, String)> {
+        let mut entries = self.nested;
+
+        if options.interpolate {
+            for (_, value) in &mut entries {
+                if value.contains('$') {
+                    *value = value.replace('$', "$$");
+                }
+            }
+        }
+
+        entries
+    }
+
+    /// Name `variant` with `self` as its payload, either following the name (`VARIANT:payload`)
+    /// or nested under the variable, see [enums].
+    ///
+    /// [enums]: crate#enums
+    pub fn into_variant(self, variant: &str) -> Result<Self> {
+        match self {
+            Self {
+                value: None,
+                nested,
+            } => Ok(Self {
+                value: Some(variant.to_owned()),
+                nested,
+            }),
+            Self {
+                value: Some(value),
+                nested,
+            } if nested.is_empty() => Ok(Self::scalar(format!("{variant}:{value}"))),
+            // The payload of a nested payload cannot be told apart.
+            Self { .. } => Err(Error::unserializable("enum")),
+        }
+    }
+}
+
+/// Serialize `value` as the node at `location`, giving it as context to errors.
+pub fn serialize<T>(options: &Options, location: Location, value: &T) -> Result<Node>
+where
+    T: Serialize + ?Sized,
+{
+    let key = location.key.clone();
+    let path = location.path.clone();
+
+    value
+        .serialize(NodeSerializer { options, location })
+        .map_err(|error| error.with_context(key, path, None, false))
+}
+
+/// Serialize the key of a map entry, which must be a single value.
+pub fn serialize_key<T>(options: &Options, location: Option<&Location>, key: &T) -> Result<String>
+where
+    T: Serialize + ?Sized,
+{
+    let node = key.serialize(NodeSerializer {
+        options,
+        location: Location::default(),
+    });
+
+    let error = match node {
+        Ok(Node {
+            value: Some(key),
+            nested,
+        }) if nested.is_empty() => return Ok(key),
+        Ok(_) => serde::ser::Error::custom("map keys must be strings, numbers or unit variants"),
+        Err(error) => error,
+    };
+
+    Err(match location {
+        Some(location) => {
+            error.with_context(location.key.clone(), location.path.clone(), None, false)
+        }
+        None => error,
+    })
+}
+
+/// The location of the variables nested under `location`.
+pub fn nested(options: &Options, location: &Location) -> Location {
+    Location {
+        key: format!(
+            "{}{}",
+            location.key,
+            options.separator.as_deref().unwrap_or_default()
+        ),
+        ..location.clone()
+    }
+}
+
+/// A [`serde::Serializer`] into a [`Node`], the reverse of its deserialization.
+struct NodeSerializer<'a> {
+    options: &'a Options,
+    location: Location,
+}
+
+macro_rules! serialize_display {
+    ($($ty:ident)*) => {
+        paste::paste! {
+            $(
+                fn [<serialize_ $ty>](self, value: $ty) -> Result<Self::Ok> {
+                    Ok(Node::scalar(value.to_string()))
+                }
+            )*
+        }
+    };
+}
+
+impl<'a> serde::Serializer for NodeSerializer<'a> {
+    type Ok = Node;
+    type Error = Error;
+    type SerializeSeq = Seq<'a>;
+    type SerializeTuple = Seq<'a>;
+    type SerializeTupleStruct = Impossible<Node, Error>;
+    type SerializeTupleVariant = Seq<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok> {
+        // The first of the configured values, so that the value is parsed back.
+        let (values, default) = if value {
+            (&self.options.truthy, "true")
+        } else {
+            (&self.options.falsy, "false")
+        };
+
+        Ok(Node::scalar(
+            values.first().map_or(default, AsRef::as_ref).to_owned(),
+        ))
+    }
+
+    serialize_display! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok> {
+        Ok(Node::scalar(value.to_owned()))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(Node::default())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        let node = value.serialize(self)?;
+
+        // e.g. `Some` of an empty map, which would be read back as `None`.
+        if node.is_empty() {
+            return Err(Error::unserializable("option"));
+        }
+
+        Ok(node)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Ok(Node::scalar(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)?.into_variant(variant)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(Seq::new(self.options, self.location, None))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Ok(Seq::new(self.options, self.location, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(Seq::new(self.options, self.location, Some(variant)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        let children = nested(self.options, &self.location);
+
+        Ok(Compound::new(
+            self.options,
+            Some(self.location),
+            children,
+            None,
+        ))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        let children = nested(self.options, &self.location);
+
+        Ok(Compound::new(
+            self.options,
+            Some(self.location),
+            children,
+            Some(variant),
+        ))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::unserializable("tuple_struct"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Err(Error::unserializable("unit_struct"))
+    }
+
+    super::unsupported_types! {
+        bytes(&[u8]) unit()
+    }
+}
@@ -62,6 +62,22 @@ fn prefixed() {
 
     assert_eq!(test_struct.a, "lorem ipsum");
     assert_eq!(test_struct.b, 128);
+
+    // Errors are the same as with `Options::prefix`, e.g. they report the prefixed name.
+    std::env::set_var("invalid_prefix_a", "lorem ipsum");
+    std::env::set_var("invalid_prefix_b", "lorem ipsum");
+
+    let error = crate::from_env_prefixed::<Test>("invalid_prefix_").unwrap_err();
+
+    assert_eq!(error.key(), Some("invalid_prefix_b"));
+    assert_eq!(
+        error.to_string(),
+        crate::Options::new()
+            .prefix("invalid_prefix_")
+            .from_env::<Test>()
+            .unwrap_err()
+            .to_string()
+    );
 }
 
 #[test]
@@ -84,3 +100,1880 @@ fn newtype() {
     assert_eq!(test.0.a, "lorem ipsum");
     assert_eq!(test.0.b, 128);
 }
+
+#[test]
+fn nested() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        a: Nested,
+        b: Option<Nested>,
+        c: Option<Nested>,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Nested {
+        a: u8,
+        b: Option<Box<Nested>>,
+    }
+
+    let options = crate::Options::new().separator("__");
+
+    let test: Test = options
+        .from_iter([("A__A", "1"), ("A__B__A", "2"), ("B__A", "3")].into_iter())
+        .unwrap();
+
+    assert_eq!(test.a.a, 1);
+    assert_eq!(test.a.b.unwrap().a, 2);
+    assert_eq!(test.b.unwrap().a, 3);
+    assert!(test.c.is_none());
+
+    assert!(crate::from_iter::<Test>([("A__A", "1")].into_iter()).is_err());
+}
+
+#[test]
+fn nested_field_precedence() {
+    #[derive(serde::Deserialize, Debug)]
+    struct Test {
+        a: Nested,
+        a_b: u8,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Nested {
+        c: u8,
+    }
+
+    let test: Test = crate::Options::new()
+        .separator("_")
+        .from_iter([("a_b", "1"), ("a_c", "2")].into_iter())
+        .unwrap();
+
+    assert_eq!(test.a_b, 1);
+    assert_eq!(test.a.c, 2);
+
+    // A struct field is only read from its nested variables.
+    let error = crate::Options::new()
+        .separator("_")
+        .from_iter::<Test>([("a", "junk"), ("a_b", "1"), ("a_c", "2")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::UnexpectedValue);
+    assert_eq!(
+        error.to_string(),
+        "`a`: expected only nested variables but found a value"
+    );
+}
+
+#[test]
+#[should_panic]
+fn nested_deny_unknown_fields() {
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize)]
+    struct Test {
+        a: Nested,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Nested {
+        b: u8,
+    }
+
+    let _test: Test = crate::Options::new()
+        .separator("__")
+        .from_iter([("a__b", "12"), ("a__c", "34")].into_iter())
+        .unwrap();
+}
+
+#[test]
+fn nested_prefixed() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        nested: Nested,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Nested {
+        value: u8,
+    }
+
+    std::env::set_var("NESTED__VALUE", "1");
+    std::env::set_var("PREFIX_NESTED__VALUE", "2");
+
+    let test: Test = crate::Options::new()
+        .prefix("PREFIX_")
+        .separator("__")
+        .from_env()
+        .unwrap();
+
+    assert_eq!(test.nested.value, 2);
+}
+
+#[test]
+fn seq() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Level {
+        Info,
+        Warn,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        hosts: Vec<std::net::IpAddr>,
+        levels: Vec<Level>,
+        empty: Vec<u8>,
+    }
+
+    let test: Test = crate::from_iter(
+        [
+            ("HOSTS", "127.0.0.1,::1"),
+            ("LEVELS", "INFO,WARN"),
+            ("EMPTY", ""),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+
+    assert_eq!(test.hosts.len(), 2);
+    assert!(matches!(test.levels[..], [Level::Info, Level::Warn]));
+    assert!(test.empty.is_empty());
+}
+
+#[test]
+fn indexed_seq() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        servers: Vec<Server>,
+        ports: Vec<u16>,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Server {
+        host: String,
+        port: u16,
+        weight: Option<u8>,
+    }
+
+    let options = crate::Options::new().separator("_");
+
+    let test: Test = options
+        .from_iter(
+            [
+                ("SERVERS_1_HOST", "b"),
+                ("SERVERS_0_HOST", "a"),
+                ("SERVERS_0_PORT", "1"),
+                ("SERVERS_1_PORT", "2"),
+                ("SERVERS_1_WEIGHT", "3"),
+                ("PORTS_0", "1"),
+                ("PORTS_1", "2"),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    assert_eq!(test.servers.len(), 2);
+    assert_eq!(test.servers[0].host, "a");
+    assert_eq!(test.servers[0].port, 1);
+    assert_eq!(test.servers[0].weight, None);
+    assert_eq!(test.servers[1].host, "b");
+    assert_eq!(test.servers[1].port, 2);
+    assert_eq!(test.servers[1].weight, Some(3));
+    assert_eq!(test.ports, [1, 2]);
+
+    let error = options
+        .from_iter::<Test>(
+            [
+                ("SERVERS_0_HOST", "a"),
+                ("SERVERS_0_PORT", "1"),
+                ("SERVERS_2_HOST", "b"),
+                ("SERVERS_2_PORT", "2"),
+                ("PORTS", ""),
+            ]
+            .into_iter(),
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "`SERVERS`: missing sequence element at index 1"
+    );
+
+    let error = options
+        .from_iter::<Test>([("SERVERS_1_HOST", "a"), ("PORTS", "")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "`SERVERS`: missing sequence element at index 0"
+    );
+
+    let error = options
+        .from_iter::<Test>([("SERVERS_01_HOST", "a"), ("PORTS", "")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "`SERVERS`: `01` is not a valid sequence index"
+    );
+}
+
+#[test]
+fn map() {
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        label: HashMap<String, String>,
+        weight: BTreeMap<String, u8>,
+        #[serde(default)]
+        empty: HashMap<String, String>,
+    }
+
+    let test: Test = crate::Options::new()
+        .separator("_")
+        .from_iter(
+            [
+                ("LABEL_REGION", "eu-west"),
+                ("LABEL_ZONE_ID", "b"),
+                ("WEIGHT_A", "1"),
+                ("WEIGHT_B", "2"),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    assert_eq!(test.label.len(), 2);
+    assert_eq!(test.label["REGION"], "eu-west");
+    assert_eq!(test.label["ZONE_ID"], "b");
+    assert_eq!(
+        test.weight,
+        BTreeMap::from([("A".into(), 1), ("B".into(), 2)])
+    );
+    assert!(test.empty.is_empty());
+
+    assert!(crate::Options::new()
+        .separator("_")
+        .from_iter::<Test>([("LABEL_A", "a"), ("WEIGHT_A", "a")].into_iter())
+        .is_err());
+}
+
+#[test]
+fn top_level_map() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let map: HashMap<String, String> =
+        crate::from_iter([("A", "lorem"), ("B", "ipsum")].into_iter()).unwrap();
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["A"], "lorem");
+    assert_eq!(map["B"], "ipsum");
+
+    let map: BTreeMap<String, u32> =
+        crate::from_iter([("A", "1"), ("B", "2")].into_iter()).unwrap();
+
+    assert_eq!(map, BTreeMap::from([("A".into(), 1), ("B".into(), 2)]));
+
+    assert!(crate::from_iter::<BTreeMap<String, u32>>([("A", "a")].into_iter()).is_err());
+
+    std::env::set_var("TOP_LEVEL_MAP_A", "1");
+    std::env::set_var("TOP_LEVEL_MAP_B", "2");
+
+    let map: BTreeMap<String, u32> = crate::from_env_prefixed("TOP_LEVEL_MAP_").unwrap();
+
+    assert_eq!(map, BTreeMap::from([("A".into(), 1), ("B".into(), 2)]));
+
+    let map: HashMap<String, String> = crate::from_env().unwrap();
+
+    assert_eq!(map["TOP_LEVEL_MAP_A"], "1");
+}
+
+#[test]
+fn flatten() {
+    use std::collections::HashMap;
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        a: u8,
+        #[serde(flatten)]
+        logging: Logging,
+        #[serde(flatten)]
+        other: HashMap<String, String>,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Logging {
+        log_level: String,
+        log_file: Option<String>,
+    }
+
+    let test: Test = crate::from_iter(
+        [
+            ("A", "1"),
+            ("LOG_LEVEL", "info"),
+            ("B", "lorem"),
+            ("C", "2"),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+
+    assert_eq!(test.a, 1);
+    assert_eq!(test.logging.log_level, "info");
+    assert_eq!(test.logging.log_file, None);
+    assert_eq!(test.other.len(), 2);
+    assert_eq!(test.other["B"], "lorem");
+    assert_eq!(test.other["C"], "2");
+}
+
+#[test]
+fn flatten_inferred() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        a: String,
+        #[serde(flatten)]
+        logging: Logging,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Logging {
+        log_json: bool,
+        log_buffer: u32,
+    }
+
+    let test: Test = crate::Options::new()
+        .infer_types()
+        .from_iter([("A", "1"), ("LOG_JSON", "true"), ("LOG_BUFFER", "64")].into_iter())
+        .unwrap();
+
+    assert_eq!(test.a, "1");
+    assert!(test.logging.log_json);
+    assert_eq!(test.logging.log_buffer, 64);
+}
+
+#[test]
+fn enum_payload() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Storage {
+        None,
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        S3 {
+            bucket: String,
+            region: Option<String>,
+        },
+        Local(std::path::PathBuf),
+        Memory(Memory),
+        Mirror(u8, u8),
+        Retries(Option<u8>),
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Memory {
+        size: u32,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        storage: Storage,
+    }
+
+    let options = crate::Options::new().separator("_");
+    let storage = |vars: &[(&'static str, &'static str)]| {
+        options
+            .from_iter::<Test>(vars.iter().copied())
+            .map(|test| test.storage)
+    };
+
+    assert_eq!(
+        storage(&[("STORAGE", "NONE"), ("STORAGE_BUCKET", "app")]).unwrap(),
+        Storage::None
+    );
+
+    assert_eq!(
+        storage(&[("STORAGE", "S3"), ("STORAGE_BUCKET", "app")]).unwrap(),
+        Storage::S3 {
+            bucket: "app".into(),
+            region: None
+        }
+    );
+
+    assert_eq!(
+        storage(&[("STORAGE", "LOCAL:/var/lib/app")]).unwrap(),
+        Storage::Local("/var/lib/app".into())
+    );
+
+    assert_eq!(
+        storage(&[("STORAGE", "MEMORY"), ("STORAGE_SIZE", "64")]).unwrap(),
+        Storage::Memory(Memory { size: 64 })
+    );
+
+    assert_eq!(
+        storage(&[("STORAGE", "MIRROR:1,2")]).unwrap(),
+        Storage::Mirror(1, 2)
+    );
+
+    // An empty payload is `None`, as it is serialized.
+    assert_eq!(
+        storage(&[("STORAGE", "RETRIES:3")]).unwrap(),
+        Storage::Retries(Some(3))
+    );
+    assert_eq!(
+        storage(&[("STORAGE", "RETRIES:")]).unwrap(),
+        Storage::Retries(None)
+    );
+    assert_eq!(
+        storage(&[("STORAGE", "RETRIES")]).unwrap(),
+        Storage::Retries(None)
+    );
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Retries {
+        Fixed(Option<u8>),
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Policy {
+        retries: Retries,
+    }
+
+    for retries in [Retries::Fixed(None), Retries::Fixed(Some(3))] {
+        let policy = Policy { retries };
+        let vars = crate::to_vec(&policy).unwrap();
+
+        assert_eq!(
+            crate::from_iter::<Policy>(vars.into_iter()).unwrap(),
+            policy
+        );
+    }
+
+    assert!(storage(&[("STORAGE", "S3")]).is_err());
+    assert!(storage(&[("STORAGE", "LOCAL")]).is_err());
+    assert!(storage(&[("STORAGE", "NONE:app")]).is_err());
+    assert!(storage(&[("STORAGE_BUCKET", "app")]).is_err());
+}
+
+#[test]
+fn internally_tagged_enum() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    #[serde(tag = "KIND", rename_all = "lowercase")]
+    enum Database {
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        Postgres { host: String, port: u16 },
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        Sqlite { path: String },
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        db: Database,
+    }
+
+    let options = crate::Options::new().separator("_").infer_types();
+
+    let test: Test = options
+        .from_iter(
+            [
+                ("DB_HOST", "localhost"),
+                ("DB_KIND", "postgres"),
+                ("DB_PORT", "5432"),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        test.db,
+        Database::Postgres {
+            host: "localhost".into(),
+            port: 5432
+        }
+    );
+
+    let database: Database =
+        crate::from_iter([("KIND", "sqlite"), ("PATH", "/var/lib/app.db")].into_iter()).unwrap();
+
+    assert_eq!(
+        database,
+        Database::Sqlite {
+            path: "/var/lib/app.db".into()
+        }
+    );
+
+    assert!(crate::from_iter::<Database>([("KIND", "mysql")].into_iter()).is_err());
+}
+
+#[test]
+fn adjacently_tagged_enum() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    #[serde(tag = "KIND", content = "CONFIG", rename_all = "lowercase")]
+    enum Database {
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        Postgres {
+            host: String,
+            port: u16,
+        },
+        Sqlite(String),
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        db: Database,
+    }
+
+    let options = crate::Options::new().separator("_");
+
+    let test: Test = options
+        .from_iter(
+            [
+                ("DB_CONFIG_HOST", "localhost"),
+                ("DB_CONFIG_PORT", "5432"),
+                ("DB_KIND", "postgres"),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        test.db,
+        Database::Postgres {
+            host: "localhost".into(),
+            port: 5432
+        }
+    );
+
+    let database: Database = options
+        .from_iter([("CONFIG", "/var/lib/app.db"), ("KIND", "sqlite")].into_iter())
+        .unwrap();
+
+    assert_eq!(database, Database::Sqlite("/var/lib/app.db".into()));
+}
+
+#[test]
+fn deserializer() {
+    use serde::Deserialize;
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Test {
+        a: String,
+        b: u8,
+    }
+
+    let options = crate::Options::new().prefix("prefix_");
+    let deserializer = crate::Deserializer::from_iter(
+        &options,
+        [
+            ("a", "wrong a"),
+            ("prefix_a", "lorem ipsum"),
+            ("prefix_b", "128"),
+        ]
+        .into_iter(),
+    );
+
+    let test = Test::deserialize(deserializer).unwrap();
+
+    assert_eq!(test.a, "lorem ipsum");
+    assert_eq!(test.b, 128);
+}
+
+#[test]
+fn error_context() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Test {
+        port: u16,
+        servers: Vec<Server>,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Server {
+        weight: u8,
+    }
+
+    let options = crate::Options::new().prefix("APP_").separator("_");
+
+    let error = options
+        .from_iter::<Test>([("APP_PORT", "eighty"), ("APP_SERVERS", "")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.key(), Some("APP_PORT"));
+    assert_eq!(error.path(), Some("PORT"));
+    assert_eq!(error.value(), Some("eighty".as_ref()));
+    assert_eq!(
+        error.to_string(),
+        "`APP_PORT`: invalid digit found in string"
+    );
+
+    let error = options
+        .from_iter::<Test>([("APP_PORT", "80"), ("APP_SERVERS_0_WEIGHT", "-1")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.key(), Some("APP_SERVERS_0_WEIGHT"));
+    assert_eq!(error.path(), Some("SERVERS.0.WEIGHT"));
+    assert_eq!(error.value(), Some("-1".as_ref()));
+
+    let error = crate::from_iter::<Test>([("PORT", "80")].into_iter()).unwrap_err();
+
+    assert_eq!(error.key(), Some("SERVERS"));
+    assert_eq!(error.to_string(), "`SERVERS`: missing field `SERVERS`");
+}
+
+#[test]
+fn collect_errors() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Test {
+        port: u16,
+        host: String,
+        debug: bool,
+        database: Database,
+        ports: Vec<u16>,
+        weight: Option<u8>,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Database {
+        host: String,
+        port: u16,
+    }
+
+    let options = crate::Options::new()
+        .prefix("APP_")
+        .separator("__")
+        .collect_errors();
+
+    let error = options
+        .from_iter::<Test>(
+            [
+                ("APP_PORT", "eighty"),
+                ("APP_DEBUG", "maybe"),
+                ("APP_DATABASE__PORT", "-1"),
+                ("APP_PORTS__0", "1"),
+                ("APP_PORTS__1", "x"),
+                ("APP_WEIGHT", "256"),
+            ]
+            .into_iter(),
+        )
+        .unwrap_err();
+
+    let keys: Vec<_> = error
+        .errors()
+        .iter()
+        .filter_map(crate::Error::key)
+        .collect();
+
+    assert_eq!(
+        keys,
+        [
+            "APP_PORT",
+            "APP_DEBUG",
+            "APP_DATABASE__PORT",
+            "APP_DATABASE__HOST",
+            "APP_PORTS__1",
+            "APP_WEIGHT",
+            "APP_HOST",
+        ]
+    );
+    assert_eq!(
+        error.to_string(),
+        "7 errors occurred:
+- `APP_PORT`: invalid digit found in string
+- `APP_DEBUG`: `maybe` is not a boolean
+- `APP_DATABASE__PORT`: invalid digit found in string
+- `APP_DATABASE__HOST`: missing field `DATABASE.HOST`
+- `APP_PORTS__1`: invalid digit found in string
+- `APP_WEIGHT`: number too large to fit in target type
+- `APP_HOST`: missing field `HOST`"
+    );
+
+    let test: Test = options
+        .from_iter(
+            [
+                ("APP_PORT", "80"),
+                ("APP_HOST", "localhost"),
+                ("APP_DEBUG", "false"),
+                ("APP_DATABASE__HOST", "localhost"),
+                ("APP_DATABASE__PORT", "5432"),
+                ("APP_PORTS", "80,443"),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    assert_eq!(test.ports, [80, 443]);
+
+    let error = options
+        .from_iter::<Test>([("APP_PORT", "eighty")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.errors().len(), 5);
+
+    let error = options
+        .from_iter::<Database>([("APP_HOST", "localhost"), ("APP_PORT", "x")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.errors().len(), 1);
+    assert_eq!(
+        error.to_string(),
+        "`APP_PORT`: invalid digit found in string"
+    );
+
+    // Types that reject placeholders, e.g. an empty string or zero.
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Server {
+        host: std::net::IpAddr,
+        port: u16,
+        workers: std::num::NonZeroU8,
+        weights: (u8, u8),
+        name: String,
+    }
+
+    let error = crate::Options::new()
+        .separator("_")
+        .collect_errors()
+        .from_iter::<Server>(
+            [
+                ("HOST", "bad"),
+                ("PORT", "bad"),
+                ("WORKERS", "0"),
+                ("WEIGHTS_0", "1"),
+                ("WEIGHTS_1", "x"),
+            ]
+            .into_iter(),
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "5 errors occurred:
+- `HOST`: invalid IP address syntax
+- `PORT`: invalid digit found in string
+- `WORKERS`: invalid value: integer `0`, expected a nonzero u8
+- `WEIGHTS_1`: invalid digit found in string
+- `NAME`: missing field `NAME`"
+    );
+}
+
+#[test]
+fn error_kind() {
+    use crate::ErrorKind;
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Test {
+        port: u16,
+        ratio: f32,
+        debug: bool,
+        host: String,
+    }
+
+    let valid = [
+        ("PORT", "80"),
+        ("RATIO", "0.5"),
+        ("DEBUG", "true"),
+        ("HOST", "localhost"),
+    ];
+
+    // Deserialize the valid variables, with `key` set to `value`.
+    let kind = |key: &'static str, value: &'static str| {
+        let vars = valid.into_iter().filter(|(name, _)| *name != key);
+
+        crate::from_iter::<Test>(vars.chain([(key, value)]))
+            .unwrap_err()
+            .kind()
+    };
+
+    assert_eq!(kind("PORT", "x"), ErrorKind::InvalidInteger);
+    assert_eq!(kind("RATIO", "x"), ErrorKind::InvalidFloat);
+    assert_eq!(kind("DEBUG", "x"), ErrorKind::InvalidBool);
+    assert_eq!(kind("USER", "x"), ErrorKind::UnknownField);
+    assert_eq!(
+        crate::from_iter::<Test>(valid[..3].iter().copied())
+            .unwrap_err()
+            .kind(),
+        ErrorKind::MissingField
+    );
+
+    let error = crate::Options::new()
+        .prefix("APP_")
+        .collect_errors()
+        .from_iter::<Test>(
+            [
+                ("APP_PORT", "80"),
+                ("APP_RATIO", "0.5"),
+                ("APP_DEBUG", "true"),
+                ("APP_HOST", "localhost"),
+                ("APP_USER", "root"),
+                ("APP_SHELL", "sh"),
+            ]
+            .into_iter(),
+        )
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::Collected);
+    assert_eq!(
+        error.to_string(),
+        "2 errors occurred:
+- `APP_USER`: unknown field `USER`, expected one of `PORT`, `RATIO`, `DEBUG`, `HOST`
+- `APP_SHELL`: unknown field `SHELL`, expected one of `PORT`, `RATIO`, `DEBUG`, `HOST`"
+    );
+}
+
+#[test]
+fn redaction() {
+    use crate::Redaction;
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Test {
+        api_token: bool,
+        level: Level,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[allow(dead_code)]
+    enum Level {
+        Info,
+        Debug,
+    }
+
+    let vars = [("APP_API_TOKEN", "hunter2"), ("APP_LEVEL", "hunter2")];
+
+    let error = crate::Options::new()
+        .prefix("APP_")
+        .redaction(Redaction::keys(["*_TOKEN"]))
+        .from_iter::<Test>(vars.into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.value(), None);
+    assert_eq!(
+        error.to_string(),
+        "`APP_API_TOKEN`: `[REDACTED]` is not a boolean"
+    );
+    assert!(!format!("{error:?}").contains("hunter2"));
+
+    let error = crate::Options::new()
+        .prefix("APP_")
+        .redaction(Redaction::keys(["*_TOKEN"]))
+        .from_iter::<Test>([("APP_API_TOKEN", "true"), ("APP_LEVEL", "hunter2")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.value(), Some("hunter2".as_ref()));
+    assert_eq!(
+        error.to_string(),
+        "`APP_LEVEL`: unknown variant `hunter2`, expected `Info` or `Debug`"
+    );
+
+    let error = crate::Options::new()
+        .prefix("APP_")
+        .redaction(Redaction::Always)
+        .from_iter::<Test>([("APP_API_TOKEN", "true"), ("APP_LEVEL", "hunter2")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.to_string(), "`APP_LEVEL`: invalid value `[REDACTED]`");
+    assert!(!format!("{error:?}").contains("hunter2"));
+
+    // Flattened values are buffered and reported by `T` without a node.
+    #[derive(serde::Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Flattened {
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Inner {
+        port: u16,
+    }
+
+    let error = crate::Options::new()
+        .redaction(Redaction::Always)
+        .from_iter::<Flattened>([("port", "hunter2")].into_iter())
+        .unwrap_err();
+
+    assert!(!error.to_string().contains("hunter2"));
+
+    // Part of the value, e.g. an element of a sequence.
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Levels {
+        levels_token: Vec<Level>,
+    }
+
+    let error = crate::Options::new()
+        .redaction(Redaction::keys(["*_TOKEN"]))
+        .from_iter::<Levels>([("LEVELS_TOKEN", "hunter2,Info")].into_iter())
+        .unwrap_err();
+
+    assert_eq!(error.value(), None);
+    assert_eq!(
+        error.to_string(),
+        "`LEVELS_TOKEN`: invalid value `[REDACTED]`"
+    );
+    assert!(!format!("{error:?}").contains("hunter2"));
+
+    // A value escaped by `T`.
+    struct Port;
+
+    impl<'de> serde::Deserialize<'de> for Port {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct Visitor;
+
+            impl serde::de::Visitor<'_> for Visitor {
+                type Value = Port;
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    formatter.write_str("a port")
+                }
+            }
+
+            deserializer.deserialize_str(Visitor)
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Escaped {
+        port: Port,
+    }
+
+    let error = crate::Options::new()
+        .redaction(Redaction::Always)
+        .from_iter::<Escaped>([("PORT", r#"hunter"2\"#)].into_iter())
+        .map(|_| ())
+        .unwrap_err();
+
+    assert_eq!(error.to_string(), "`PORT`: invalid value `[REDACTED]`");
+
+    // A redacted value interpolated into another variable.
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[allow(dead_code)]
+    struct Interpolated {
+        level: Level,
+    }
+
+    let error = crate::Options::new()
+        .interpolate()
+        .redaction(Redaction::keys(["*_TOKEN"]))
+        .from_iter::<Interpolated>(
+            [("API_TOKEN", "hunter2"), ("LEVEL", "${API_TOKEN}")].into_iter(),
+        )
+        .unwrap_err();
+
+    assert_eq!(error.value(), None);
+    assert_eq!(error.to_string(), "`LEVEL`: invalid value `[REDACTED]`");
+    // `unwrap` and `main` print errors with `Debug`.
+    assert!(!format!("{error:?}").contains("hunter2"));
+}
+
+#[test]
+fn file_suffix() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
+    struct Test {
+        password: String,
+        user: String,
+        database: Database,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Database {
+        port: u16,
+    }
+
+    let directory = std::env::temp_dir().join(format!("de_env_file_suffix_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let password = directory.join("password");
+    let port = directory.join("port");
+    std::fs::write(&password, "hunter2\r\n").unwrap();
+    std::fs::write(&port, "5432\n").unwrap();
+
+    let options = crate::Options::new()
+        .prefix("APP_")
+        .separator("_")
+        .file_suffix("_FILE");
+
+    let test: Test = options
+        .from_iter(
+            [
+                ("APP_PASSWORD_FILE", password.as_os_str()),
+                ("APP_USER", "root".as_ref()),
+                ("APP_USER_FILE", password.as_os_str()),
+                ("APP_DATABASE_PORT_FILE", port.as_os_str()),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    assert_eq!(test.password, "hunter2");
+    assert_eq!(test.user, "root");
+    assert_eq!(test.database.port, 5432);
+
+    let missing = directory.join("missing");
+
+    let error = options
+        .from_iter::<Test>(
+            [
+                ("APP_PASSWORD_FILE", missing.as_os_str()),
+                ("APP_USER", "root".as_ref()),
+                ("APP_DATABASE_PORT", "5432".as_ref()),
+            ]
+            .into_iter(),
+        )
+        .unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::ReadFile);
+    assert_eq!(error.key(), Some("APP_PASSWORD_FILE"));
+    assert_eq!(error.path(), Some("PASSWORD"));
+    assert!(std::error::Error::source(&error).is_some());
+    assert!(error.to_string().starts_with(&format!(
+        "`APP_PASSWORD_FILE`: could not read `{}`: ",
+        missing.display()
+    )));
+
+    let error = options
+        .clone()
+        .collect_errors()
+        .from_iter::<Test>([("APP_PASSWORD_FILE", missing.as_os_str())].into_iter())
+        .unwrap_err();
+
+    let keys: Vec<_> = error
+        .errors()
+        .iter()
+        .filter_map(crate::Error::key)
+        .collect();
+
+    assert_eq!(keys, ["APP_PASSWORD_FILE", "APP_USER", "APP_DATABASE"]);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn from_dir() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
+    struct Test {
+        port: u16,
+        host: String,
+    }
+
+    let directory = std::env::temp_dir().join(format!("de_env_from_dir_{}", std::process::id()));
+    std::fs::create_dir_all(directory.join("APP_CONFIG")).unwrap();
+    std::fs::create_dir_all(directory.join("..data")).unwrap();
+    std::fs::write(directory.join("APP_PORT"), "80\n").unwrap();
+    std::fs::write(directory.join("APP_HOST"), "localhost").unwrap();
+    std::fs::write(directory.join("OTHER"), "").unwrap();
+    std::fs::write(directory.join(".APP_HIDDEN"), "").unwrap();
+
+    let options = crate::Options::new().prefix("APP_");
+
+    let test: Test = options.from_dir(&directory).unwrap();
+
+    assert_eq!(test.port, 80);
+    assert_eq!(test.host, "localhost");
+
+    let error = options
+        .clone()
+        .reject_subdirectories()
+        .from_dir::<Test>(&directory)
+        .unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::UnexpectedDirectory);
+    assert_eq!(error.key(), Some("APP_CONFIG"));
+    assert_eq!(error.path(), Some("CONFIG"));
+
+    let error = options
+        .from_dir::<Test>(directory.join("missing"))
+        .unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::ReadFile);
+    assert_eq!(error.key(), None);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn dotenv() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        host: String,
+        database: Database,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Database {
+        port: u16,
+    }
+
+    let input = "
+        # Comment
+        export APP_HOST='local host'
+        APP_DATABASE__PORT=\"5432\"
+        OTHER=1
+    ";
+
+    let options = crate::Options::new().prefix("APP_").separator("__");
+
+    let test: Test = options.from_dotenv_str(input).unwrap();
+
+    assert_eq!(test.host, "local host");
+    assert_eq!(test.database.port, 5432);
+
+    let test: Test = options.from_dotenv_reader(input.as_bytes()).unwrap();
+
+    assert_eq!(test.host, "local host");
+
+    let error = options
+        .from_dotenv_str::<Test>("APP_HOST=localhost\nAPP_DATABASE__PORT=x")
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "`APP_DATABASE__PORT`: invalid digit found in string"
+    );
+
+    let error = crate::from_dotenv_str::<Test>("HOST=\"localhost").unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::InvalidDotenv);
+
+    let error = crate::from_dotenv_path::<Test>("/nonexistent/.env").unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::ReadFile);
+}
+
+#[test]
+fn interpolate() {
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        url: String,
+        port: u16,
+        database: Database,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Database {
+        name: String,
+    }
+
+    let options = crate::Options::new()
+        .prefix("APP_")
+        .separator("__")
+        .interpolate();
+
+    let test: Test = options
+        .from_iter(
+            [
+                ("HOST", "localhost"),
+                ("APP_PORT", "${PORT:-8080}"),
+                ("APP_URL", "http://${HOST}:${APP_PORT}/$${path}"),
+                ("APP_DATABASE__NAME", "${HOST}_db"),
+                ("APP_UNUSED", "${UNUSED"),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    assert_eq!(test.url, "http://localhost:8080/${path}");
+    assert_eq!(test.port, 8080);
+    assert_eq!(test.database.name, "localhost_db");
+
+    // Disabled by default.
+    let test: Test = crate::Options::new()
+        .prefix("APP_")
+        .separator("__")
+        .from_iter(
+            [
+                ("APP_URL", "${HOST}"),
+                ("APP_PORT", "1"),
+                ("APP_DATABASE__NAME", "$$"),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    assert_eq!(test.url, "${HOST}");
+    assert_eq!(test.database.name, "$$");
+
+    let error = options
+        .from_iter::<Test>(
+            [
+                ("A", "${B}"),
+                ("B", "${A}"),
+                ("APP_URL", "${A}"),
+                ("APP_PORT", "1"),
+                ("APP_DATABASE__NAME", "x"),
+            ]
+            .into_iter(),
+        )
+        .unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::ReferenceCycle);
+    assert_eq!(error.key(), Some("APP_URL"));
+    assert_eq!(
+        error.to_string(),
+        "`APP_URL`: reference cycle: `APP_URL` -> `A` -> `B` -> `A`"
+    );
+
+    let error = options
+        .clone()
+        .collect_errors()
+        .from_iter::<Test>(
+            [
+                ("APP_URL", "${HOST:?required}"),
+                ("APP_PORT", "${HOST}"),
+                ("APP_DATABASE__NAME", "x"),
+            ]
+            .into_iter(),
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "2 errors occurred:
+- `APP_URL`: missing variable `HOST`, referenced through `APP_URL` -> `HOST`: required
+- `APP_PORT`: cannot parse integer from empty string"
+    );
+
+    let test: Test = options
+        .from_dotenv_str(
+            "HOST=localhost\nAPP_URL=\"${HOST}\"\nAPP_PORT=1\nAPP_DATABASE__NAME='${HOST}'",
+        )
+        .unwrap();
+
+    assert_eq!(test.url, "localhost");
+    assert_eq!(test.database.name, "${HOST}");
+
+    // Single-quoted values and `\$` escapes are taken literally.
+    let test: Test = options
+        .from_dotenv_str(
+            "HOST=localhost\n\
+            APP_URL=\"\\${HOST} $${HOST}\"\n\
+            APP_PORT=1\n\
+            APP_DATABASE__NAME='$${HOST}'",
+        )
+        .unwrap();
+
+    assert_eq!(test.url, "${HOST} ${HOST}");
+    assert_eq!(test.database.name, "$${HOST}");
+}
+
+#[test]
+fn serialize() {
+    use std::collections::BTreeMap;
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        enabled: bool,
+        ratio: f64,
+        initial: char,
+        name: String,
+        tags: Vec<String>,
+        empty: Vec<String>,
+        blank: Vec<String>,
+        proxy: Option<String>,
+        pair: (u8, i64),
+        database: Database,
+        servers: Vec<Server>,
+        labels: BTreeMap<String, u16>,
+        mode: Mode,
+        modes: Vec<Mode>,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Database {
+        host: String,
+        port: Option<u16>,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Server {
+        host: String,
+        mode: Mode,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Mode {
+        Off,
+        Fixed(u16),
+        Range(u16, u16),
+        Custom { rate: u16 },
+    }
+
+    let test = Test {
+        enabled: true,
+        ratio: 0.25,
+        initial: 'é',
+        name: "a, \"quoted\" \\ name".to_owned(),
+        tags: vec![
+            "a,b".to_owned(),
+            "\"c\"".to_owned(),
+            String::new(),
+            r"d\".to_owned(),
+        ],
+        empty: Vec::new(),
+        blank: vec![String::new()],
+        proxy: None,
+        pair: (1, -2),
+        database: Database {
+            host: "localhost".to_owned(),
+            port: None,
+        },
+        servers: vec![
+            Server {
+                host: "a".to_owned(),
+                mode: Mode::Off,
+            },
+            Server {
+                host: "b".to_owned(),
+                mode: Mode::Custom { rate: 3 },
+            },
+        ],
+        labels: BTreeMap::from([("team".to_owned(), 1), ("tier".to_owned(), 2)]),
+        mode: Mode::Range(1, 2),
+        modes: vec![Mode::Off, Mode::Fixed(4)],
+    };
+
+    let options = crate::Options::new().prefix("APP_").separator("__");
+    let vars = options.to_vec(&test).unwrap();
+
+    assert_eq!(
+        vars,
+        [
+            ("APP_ENABLED", "true"),
+            ("APP_RATIO", "0.25"),
+            ("APP_INITIAL", "é"),
+            ("APP_NAME", "a, \"quoted\" \\ name"),
+            ("APP_TAGS", r#"a\,b,\"c\","",d\\"#),
+            ("APP_EMPTY", ""),
+            ("APP_BLANK", r#""""#),
+            ("APP_PAIR", "1,-2"),
+            ("APP_DATABASE__HOST", "localhost"),
+            ("APP_SERVERS__0__HOST", "a"),
+            ("APP_SERVERS__0__MODE", "OFF"),
+            ("APP_SERVERS__1__HOST", "b"),
+            ("APP_SERVERS__1__MODE", "CUSTOM"),
+            ("APP_SERVERS__1__MODE__rate", "3"),
+            ("APP_LABELS__team", "1"),
+            ("APP_LABELS__tier", "2"),
+            ("APP_MODE", "RANGE:1,2"),
+            ("APP_MODES", "OFF,FIXED:4"),
+        ]
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+    );
+
+    assert_eq!(options.from_iter::<Test>(vars.into_iter()).unwrap(), test);
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Flattened {
+        port: u16,
+        #[serde(flatten)]
+        logging: Logging,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Logging {
+        log_level: String,
+    }
+
+    let flattened = Flattened {
+        port: 8080,
+        logging: Logging {
+            log_level: "info".to_owned(),
+        },
+    };
+
+    let vars = crate::to_vec(&flattened).unwrap();
+
+    assert_eq!(
+        crate::from_iter::<Flattened>(vars.into_iter()).unwrap(),
+        flattened
+    );
+
+    // References are escaped if interpolation is enabled.
+    let options = crate::Options::new().interpolate();
+
+    let logging = Logging {
+        log_level: "$HOME ${MISSING:?unset} $$ $".to_owned(),
+    };
+
+    let vars = options.to_vec(&logging).unwrap();
+
+    assert_eq!(
+        vars,
+        [(
+            "LOG_LEVEL".to_owned(),
+            "$$HOME $${MISSING:?unset} $$$$ $$".to_owned()
+        )]
+    );
+    assert_eq!(
+        options.from_iter::<Logging>(vars.into_iter()).unwrap(),
+        logging
+    );
+
+    let dotenv = options.to_string(&logging, crate::Format::Dotenv).unwrap();
+
+    assert_eq!(
+        options.from_dotenv_str::<Logging>(&dotenv).unwrap(),
+        logging
+    );
+    assert_eq!(crate::to_vec(&logging).unwrap()[0].1, logging.log_level);
+
+    // Booleans follow the configured values.
+    let options = crate::Options::new().truthy(["on"]).falsy(["off"]);
+
+    assert_eq!(
+        options
+            .to_iter(&BTreeMap::from([("A", true), ("B", false)]))
+            .unwrap()
+            .collect::<Vec<_>>(),
+        [("A", "on"), ("B", "off")].map(|(key, value)| (key.to_owned(), value.to_owned()))
+    );
+
+    // Nested variables require a separator.
+    let error = crate::to_vec(&test).unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::UnsupportedType);
+    assert_eq!(
+        error.to_string(),
+        "`DATABASE`: nested variables cannot be serialized without a separator"
+    );
+
+    let error = crate::to_vec(&[1, 2]).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "`tuple` cannot be serialized to environment variables"
+    );
+
+    let error = crate::to_vec(&BTreeMap::from([("UNIT", ())])).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "`UNIT`: `unit` cannot be serialized to environment variables"
+    );
+
+    let error = crate::to_vec(&BTreeMap::from([("LIST", [Some(1), None])])).unwrap_err();
+
+    assert_eq!(error.key(), Some("LIST1"));
+    assert_eq!(
+        error.to_string(),
+        "`LIST1`: `none` cannot be serialized to environment variables"
+    );
+
+    // Values without variables, e.g. empty maps.
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Empty {
+        #[serde(default)]
+        labels: BTreeMap<String, u16>,
+        inner: Option<Inner>,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Inner {
+        port: Option<u16>,
+    }
+
+    let options = crate::Options::new().separator("_");
+
+    for empty in [
+        Empty {
+            labels: BTreeMap::new(),
+            inner: None,
+        },
+        Empty {
+            labels: BTreeMap::new(),
+            inner: Some(Inner { port: Some(1) }),
+        },
+    ] {
+        let vars = options.to_vec(&empty).unwrap();
+
+        assert_eq!(options.from_iter::<Empty>(vars.into_iter()).unwrap(), empty);
+    }
+
+    let error = options
+        .to_vec(&Empty {
+            labels: BTreeMap::new(),
+            inner: Some(Inner { port: None }),
+        })
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "`INNER`: `option` cannot be serialized to environment variables"
+    );
+}
+
+#[test]
+fn apply_to_command() {
+    use std::{ffi::OsStr, process::Command};
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        id: u16,
+        queue: Option<String>,
+    }
+
+    let test = Test {
+        id: 1,
+        queue: Some("jobs".to_owned()),
+    };
+
+    let envs = |command: &Command| {
+        command
+            .get_envs()
+            .map(|(key, value)| (key.to_owned(), value.map(OsStr::to_owned)))
+            .collect::<Vec<_>>()
+    };
+
+    let mut command = Command::new("worker");
+    command.env("OTHER", "1");
+
+    crate::apply_to_command(&test, &mut command).unwrap();
+
+    assert_eq!(
+        envs(&command),
+        [
+            ("ID", Some("1")),
+            ("OTHER", Some("1")),
+            ("QUEUE", Some("jobs"))
+        ]
+        .map(|(key, value)| (key.into(), value.map(Into::into)))
+    );
+
+    let mut command = Command::new("worker");
+    command.env("OTHER", "1");
+
+    crate::Options::new()
+        .prefix("WORKER_")
+        .clear_env()
+        .apply_to_command(&test, &mut command)
+        .unwrap();
+
+    assert_eq!(
+        envs(&command),
+        [("WORKER_ID", Some("1")), ("WORKER_QUEUE", Some("jobs"))]
+            .map(|(key, value)| (key.into(), value.map(Into::into)))
+    );
+
+    // The command is left untouched on error.
+    let mut command = Command::new("worker");
+    command.env("OTHER", "1");
+
+    assert!(crate::Options::new()
+        .clear_env()
+        .apply_to_command(&[1], &mut command)
+        .is_err());
+    assert_eq!(envs(&command).len(), 1);
+}
+
+#[test]
+fn to_writer() {
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        motd: String,
+        hosts: Vec<String>,
+        database: Database,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Database {
+        password: String,
+    }
+
+    let test = Test {
+        motd: "Welcome,\n\"$USER\"".to_owned(),
+        hosts: vec!["a".to_owned(), "b,c".to_owned()],
+        database: Database {
+            password: "p#ss' word".to_owned(),
+        },
+    };
+
+    let options = crate::Options::new().prefix("APP_").separator("__");
+
+    let mut output = Vec::new();
+    options
+        .to_writer(&test, crate::Format::Dotenv, &mut output)
+        .unwrap();
+
+    assert_eq!(
+        options
+            .from_dotenv_reader::<Test>(output.as_slice())
+            .unwrap(),
+        test
+    );
+
+    let error = options.to_string(&test, crate::Format::Docker).unwrap_err();
+
+    assert_eq!(error.kind(), crate::ErrorKind::UnsupportedValue);
+    assert_eq!(error.key(), Some("APP_MOTD"));
+}
+
+#[test]
+fn describe() {
+    use std::{collections::HashMap, net::IpAddr};
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Test {
+        host: IpAddr,
+        port: u16,
+        debug: Option<bool>,
+        level: Level,
+        tags: Vec<String>,
+        levels: Vec<Level>,
+        range: (u8, i64),
+        database: Option<Database>,
+        servers: Vec<Server>,
+        labels: HashMap<String, u8>,
+        password: String,
+        storage: Storage,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Level {
+        Debug,
+        Info,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Database {
+        url: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Server {
+        address: IpAddr,
+        weight: f32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Storage {
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        S3 {
+            bucket: String,
+        },
+        Memory,
+    }
+
+    let description = crate::Options::new()
+        .prefix("APP_")
+        .separator("__")
+        .describe::<Test>();
+
+    let variables: Vec<(&str, &str, &str, bool, &[&str])> = description
+        .variables()
+        .iter()
+        .map(|variable| {
+            (
+                variable.key(),
+                variable.path(),
+                variable.ty(),
+                variable.is_required(),
+                variable.variants(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        variables,
+        [
+            ("APP_HOST", "HOST", "string", true, &[][..]),
+            ("APP_PORT", "PORT", "u16", true, &[]),
+            ("APP_DEBUG", "DEBUG", "bool", false, &[]),
+            ("APP_LEVEL", "LEVEL", "enum", true, &["DEBUG", "INFO"]),
+            ("APP_TAGS", "TAGS", "list of string", true, &[]),
+            (
+                "APP_LEVELS",
+                "LEVELS",
+                "list of enum",
+                true,
+                &["DEBUG", "INFO"]
+            ),
+            ("APP_RANGE", "RANGE", "list of u8, i64", true, &[]),
+            ("APP_DATABASE__URL", "DATABASE.URL", "string", false, &[]),
+            (
+                "APP_SERVERS__0__ADDRESS",
+                "SERVERS.0.ADDRESS",
+                "string",
+                true,
+                &[]
+            ),
+            ("APP_LABELS__*", "LABELS.*", "u8", true, &[]),
+            ("APP_PASSWORD", "PASSWORD", "string", true, &[]),
+            ("APP_STORAGE", "STORAGE", "enum", true, &["S3", "MEMORY"]),
+            ("APP_STORAGE__BUCKET", "STORAGE.BUCKET", "string", true, &[]),
+            (
+                "APP_SERVERS__0__WEIGHT",
+                "SERVERS.0.WEIGHT",
+                "f32",
+                true,
+                &[]
+            ),
+        ]
+    );
+
+    assert_eq!(
+        crate::describe::<Database>().to_markdown(),
+        "| Variable | Type | Required | Values |\n|---|---|---|---|\n| `URL` | `string` | yes | |\n"
+    );
+
+    let description = crate::Options::new().separator("_").describe::<Test>();
+    let example = description.to_env_example();
+
+    assert!(example.starts_with("# string\nHOST=\n\n# u16\nPORT=\n\n# bool (optional)\n# DEBUG=\n"));
+    assert!(example.contains("\n# enum, one of DEBUG, INFO\nLEVEL=\n"));
+    assert!(example.contains("\n# u8\n# LABELS_*=\n"));
+
+    // Recursive fields are described as unsupported.
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Node {
+        value: u8,
+        next: Option<Box<Node>>,
+        children: Vec<Node>,
+        expr: Expr,
+        name: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Expr {
+        Neg(Box<Expr>),
+        Literal(u8),
+    }
+
+    let description = crate::Options::new().separator("__").describe::<Node>();
+    let types: Vec<(&str, &str)> = description
+        .variables()
+        .iter()
+        .map(|variable| (variable.key(), variable.ty()))
+        .collect();
+
+    assert_eq!(
+        types,
+        [
+            ("VALUE", "u8"),
+            ("NEXT", "unsupported"),
+            ("CHILDREN", "list of unsupported"),
+            ("EXPR", "enum"),
+            ("NAME", "string"),
+        ]
+    );
+
+    // Flattened and internally tagged types cannot be traced.
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Tagged {
+        port: u16,
+        storage: Internal,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    #[serde(tag = "TYPE")]
+    enum Internal {
+        Memory,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, Debug)]
+    struct Flattened {
+        #[serde(flatten)]
+        tagged: Tagged,
+    }
+
+    assert_eq!(
+        crate::describe::<Tagged>().to_markdown(),
+        "| Variable | Type | Required | Values |\n|---|---|---|---|\n\
+        | `PORT` | `u16` | yes | |\n\
+        | `STORAGE` | `unsupported` | yes | |\n"
+    );
+    assert_eq!(
+        crate::describe::<Flattened>().to_markdown(),
+        "| Variable | Type | Required | Values |\n|---|---|---|---|\n\
+        | `*` | `unsupported` | yes | |\n"
+    );
+
+    // Nested variables are not read without a separator.
+    let description = crate::describe::<Test>();
+
+    let types: Vec<(&str, &str)> = description
+        .variables()
+        .iter()
+        .filter(|variable| ["DATABASE", "LABELS"].contains(&variable.key()))
+        .map(|variable| (variable.key(), variable.ty()))
+        .collect();
+
+    assert_eq!(
+        types,
+        [("DATABASE", "unsupported"), ("LABELS", "unsupported")]
+    );
+    assert!(description
+        .variables()
+        .iter()
+        .all(|variable| !variable.key().starts_with("DATABASEURL")));
+}